@@ -0,0 +1,129 @@
+// primary visibility of the sphere demo, one ray at a time against packets
+// of 4 and 8 neighbouring pixels culled by the bounds of the spheres
+//
+//     cargo bench --bench packet
+
+use scintilla::geo::{Ray, RayPacket, Shape, Sphere};
+use scintilla::matrix::{Matrix4, Transform};
+use scintilla::scene::Camera;
+use scintilla::tuple::{Point3, Vector3};
+use std::f64::consts::PI;
+use std::hint::black_box;
+use std::time::Instant;
+
+const WIDTH: usize = 480;
+const HEIGHT: usize = 270;
+const ROUNDS: usize = 5;
+
+// the spheres and the camera of putting_it_together6, without the motion
+fn scene() -> (Vec<Sphere>, Camera) {
+    let spheres = (0..3)
+        .map(|i| {
+            let mut sphere = Sphere::unit();
+            sphere.transform = Transform::new(Matrix4::translation_matrix(
+                -2.5 + 2.5 * i as f64,
+                0.,
+                3. * i as f64,
+            ));
+            sphere
+        })
+        .collect();
+    let mut camera = Camera::new(WIDTH, HEIGHT, PI / 3.);
+    camera.transform = Transform::new(Matrix4::view_transform(
+        Point3::point(0., 1.5, -8.),
+        Point3::point(0., 0., 3.),
+        Vector3::vector(0., 1., 0.),
+    ));
+    (spheres, camera)
+}
+
+// the distance to the closest hit
+fn trace(ray: &Ray, spheres: &[Sphere]) -> Option<f64> {
+    spheres
+        .iter()
+        .filter_map(|sphere| sphere.intersections(ray).hit().map(|hit| hit.t))
+        .min_by(f64::total_cmp)
+}
+
+fn trace_packet<const N: usize>(packet: &RayPacket<N>, spheres: &[Sphere]) -> [Option<f64>; N] {
+    let mut closest = [None; N];
+    for sphere in spheres {
+        if !sphere
+            .world_bounds()
+            .intersects_packet(packet)
+            .contains(&true)
+        {
+            continue;
+        }
+        for (closest, xs) in closest.iter_mut().zip(sphere.intersections_packet(packet)) {
+            if let Some(hit) = xs.hit() {
+                if closest.is_none_or(|t| hit.t < t) {
+                    *closest = Some(hit.t);
+                }
+            }
+        }
+    }
+    closest
+}
+
+fn packets<const N: usize>(camera: &Camera) -> Vec<(RayPacket<N>, [bool; N])> {
+    (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).step_by(N).map(move |x| (x, y)))
+        .filter_map(|(x, y)| camera.packet_for_pixels(x, y))
+        .collect()
+}
+
+// best time per ray over a few rounds, in nanoseconds
+fn measure<F: FnMut()>(name: &str, mut run: F) -> f64 {
+    let mut best = f64::INFINITY;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        run();
+        best = best.min(start.elapsed().as_secs_f64() * 1e9 / (WIDTH * HEIGHT) as f64);
+    }
+    println!("{:<28}{:>8.1} ns/ray", name, best);
+    best
+}
+
+fn main() {
+    let (spheres, camera) = scene();
+    let rays = (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .filter_map(|(x, y)| camera.ray_for_pixel(x, y))
+        .collect::<Vec<_>>();
+    let (packets4, packets8) = (packets::<4>(&camera), packets::<8>(&camera));
+    // the packets see the same hits as the single rays
+    let hits = rays
+        .iter()
+        .filter(|ray| trace(ray, &spheres).is_some())
+        .count();
+    // only the active lanes count
+    let packet_hits = packets8
+        .iter()
+        .flat_map(|(packet, mask)| trace_packet(packet, &spheres).into_iter().zip(mask))
+        .filter(|(hit, active)| hit.is_some() && **active)
+        .count();
+    assert_eq!(hits, packet_hits);
+    println!("{} rays, {} hits", rays.len(), hits);
+
+    let single = measure("single rays", || {
+        for ray in &rays {
+            black_box(trace(black_box(ray), &spheres));
+        }
+    });
+    let four = measure("packets of 4", || {
+        for (packet, _) in &packets4 {
+            black_box(trace_packet(black_box(packet), &spheres));
+        }
+    });
+    let eight = measure("packets of 8", || {
+        for (packet, _) in &packets8 {
+            black_box(trace_packet(black_box(packet), &spheres));
+        }
+    });
+    println!(
+        "packets of 4 are {:.1}x and packets of 8 {:.1}x faster",
+        single / four,
+        single / eight
+    );
+}
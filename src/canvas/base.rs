@@ -0,0 +1,323 @@
+use crate::canvas::{ExrPrecision, OutputTransform};
+use crate::color::{Color, Rgba};
+use image::{ImageFormat, ImageResult, Rgb, Rgb32FImage, RgbImage};
+use std::ops::{Add, Mul};
+use std::path::Path;
+
+// what a canvas can hold, pixels are filtered as weighted sums while rendering
+pub trait Pixel: Copy + Add<Output = Self> + Mul<f64, Output = Self> + Send + Sync {
+    // the number of float channels, also stored in checkpoints
+    const CHANNELS: usize;
+
+    // black or fully transparent
+    fn zero() -> Self;
+
+    // drives the adaptive sampling
+    fn luminance(&self) -> f64;
+
+    fn channels(&self) -> Vec<f64>;
+
+    fn from_channels(channels: &[f64]) -> Self;
+
+    fn save_canvas(canvas: &Canvas<Self>, path: &Path) -> ImageResult<()>;
+}
+
+impl Pixel for Color {
+    const CHANNELS: usize = 3;
+
+    fn zero() -> Self {
+        Color::black()
+    }
+
+    fn luminance(&self) -> f64 {
+        Color::luminance(self)
+    }
+
+    fn channels(&self) -> Vec<f64> {
+        vec![self.r, self.g, self.b]
+    }
+
+    fn from_channels(channels: &[f64]) -> Self {
+        Color::new(channels[0], channels[1], channels[2])
+    }
+
+    fn save_canvas(canvas: &Canvas<Self>, path: &Path) -> ImageResult<()> {
+        canvas.save(path)
+    }
+}
+
+impl Pixel for Rgba {
+    const CHANNELS: usize = 4;
+
+    fn zero() -> Self {
+        Rgba::transparent()
+    }
+
+    // premultiplied, so that edges against nothing count as contrast
+    fn luminance(&self) -> f64 {
+        self.color.luminance()
+    }
+
+    fn channels(&self) -> Vec<f64> {
+        vec![self.color.r, self.color.g, self.color.b, self.alpha]
+    }
+
+    fn from_channels(channels: &[f64]) -> Self {
+        Rgba {
+            color: Color::new(channels[0], channels[1], channels[2]),
+            alpha: channels[3],
+        }
+    }
+
+    fn save_canvas(canvas: &Canvas<Self>, path: &Path) -> ImageResult<()> {
+        canvas.save(path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas<P = Color> {
+    buffer: Vec<P>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl<P: Copy> Canvas<P> {
+    pub fn new(width: usize, height: usize, color: P) -> Self {
+        Self {
+            width,
+            height,
+            buffer: vec![color; width * height],
+        }
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<P>) -> Self {
+        assert_eq!(pixels.len(), width * height);
+        Self {
+            width,
+            height,
+            buffer: pixels,
+        }
+    }
+
+    // row major, y * width + x
+    pub fn pixels(&self) -> &[P] {
+        &self.buffer
+    }
+
+    pub fn read_pixel(&self, x: usize, y: usize) -> &P {
+        &self.buffer[y * self.width + x]
+    }
+
+    pub fn write_pixel(&mut self, x: usize, y: usize, color: P) {
+        assert!(x < self.width && y < self.height);
+        self.buffer[y * self.width + x] = color
+    }
+}
+
+impl Canvas {
+    pub fn to_ppm(&self) -> String {
+        self.to_ppm_with(&OutputTransform::default())
+    }
+
+    pub fn to_ppm_with(&self, output: &OutputTransform) -> String {
+        let mut ppm = String::new();
+        ppm.push_str("P3\n");
+        ppm.push_str(&format!("{} {}\n", self.width, self.height));
+        ppm.push_str("255\n");
+        let mut is_new_line = true;
+        let mut current_line_lenght: usize = 0;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let channels = output.quantize(*self.read_pixel(x, y), x, y, 255);
+                for ppm_channel in channels.map(|channel| channel.to_string()) {
+                    if current_line_lenght + ppm_channel.chars().count() + 1 > 70 {
+                        ppm.push('\n');
+                        current_line_lenght = 0;
+                        is_new_line = true;
+                    }
+                    if !is_new_line {
+                        ppm.push(' ');
+                        current_line_lenght += 1;
+                    }
+                    ppm.push_str(&ppm_channel);
+                    is_new_line = false;
+                    current_line_lenght += ppm_channel.chars().count();
+                }
+            }
+            ppm.push('\n');
+            current_line_lenght = 0;
+            is_new_line = true;
+        }
+        ppm
+    }
+
+    // 8 bits per channel, clamped and rounded like the ppm output
+    pub fn to_rgb_image(&self) -> RgbImage {
+        self.to_rgb_image_with(&OutputTransform::default())
+    }
+
+    pub fn to_rgb_image_with(&self, output: &OutputTransform) -> RgbImage {
+        RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let (x, y) = (x as usize, y as usize);
+            Rgb(output
+                .quantize(*self.read_pixel(x, y), x, y, 255)
+                .map(|channel| channel as u8))
+        })
+    }
+
+    pub fn from_rgb_image(image: &RgbImage) -> Self {
+        let pixels = image
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b] = pixel.0.map(|channel| channel as f64 / 255.);
+                Color::new(r, g, b)
+            })
+            .collect();
+        Self::from_pixels(image.width() as usize, image.height() as usize, pixels)
+    }
+
+    // read any format the image crate knows, 8 bits per channel
+    pub fn load(path: &Path) -> ImageResult<Self> {
+        Ok(Self::from_rgb_image(&image::open(path)?.to_rgb8()))
+    }
+
+    // unclamped float channels for the formats that can store them
+    pub fn to_rgb32f_image(&self) -> Rgb32FImage {
+        Rgb32FImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let color = self.read_pixel(x as usize, y as usize);
+            Rgb([color.r as f32, color.g as f32, color.b as f32])
+        })
+    }
+
+    // the format is guessed from the extension of `path`
+    pub fn save(&self, path: &Path) -> ImageResult<()> {
+        self.save_with(path, &OutputTransform::default())
+    }
+
+    pub fn save_with(&self, path: &Path, output: &OutputTransform) -> ImageResult<()> {
+        self.save_with_format(path, ImageFormat::from_path(path)?, output)
+    }
+
+    // high dynamic range formats keep the linear values and ignore `output`
+    pub fn save_with_format(
+        &self,
+        path: &Path,
+        format: ImageFormat,
+        output: &OutputTransform,
+    ) -> ImageResult<()> {
+        match format {
+            ImageFormat::OpenExr => self.save_exr(path, ExrPrecision::Float, &[]),
+            ImageFormat::Hdr => self.save_hdr(path),
+            _ => self
+                .to_rgb_image_with(output)
+                .save_with_format(path, format),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Canvas;
+    use crate::color::Color;
+    use indoc::indoc;
+    use std::path::Path;
+    use std::{env, fs, process};
+
+    #[test]
+    fn it_creates_canvases() {
+        let canvas = Canvas::new(10, 20, Color::new(0., 0., 0.));
+        assert_eq!(canvas.width, 10);
+        assert_eq!(canvas.height, 20);
+        assert_eq!(canvas.read_pixel(5, 10), &Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn it_write_pixels() {
+        let mut canvas = Canvas::new(10, 20, Color::new(0., 0., 0.));
+        let red = Color::new(1., 0., 0.);
+        canvas.write_pixel(2, 3, red);
+        assert_eq!(canvas.read_pixel(2, 3), &red);
+    }
+
+    #[test]
+    fn it_constructs_the_ppm_header() {
+        let canvas = Canvas::new(5, 3, Color::new(0., 0., 0.));
+        let ppm = canvas.to_ppm();
+        let expected = indoc! {"P3
+                             5 3
+                             255"};
+        let lines = &(ppm.split("\n").collect::<Vec<&str>>())[..3].join("\n");
+        assert_eq!(lines, expected);
+        assert!(ppm.starts_with(expected));
+    }
+
+    #[test]
+    fn it_constructs_the_ppm_pixel_data() {
+        let mut canvas = Canvas::new(5, 3, Color::new(0., 0., 0.));
+        let color1 = Color::new(1.5, 0., 0.);
+        let color2 = Color::new(0., 0.5, 0.);
+        let color3 = Color::new(-0.5, 0., 1.);
+        canvas.write_pixel(0, 0, color1);
+        canvas.write_pixel(2, 1, color2);
+        canvas.write_pixel(4, 2, color3);
+
+        let ppm = canvas.to_ppm();
+
+        let expected = indoc! {"255 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+                                    0 0 0 0 0 0 0 128 0 0 0 0 0 0 0
+                                    0 0 0 0 0 0 0 0 0 0 0 0 0 0 255"};
+        let lines = &(ppm.split("\n").collect::<Vec<&str>>())[3..6].join("\n");
+        assert_eq!(expected, lines);
+    }
+
+    #[test]
+    fn it_splits_long_lines_in_the_ppm_pixel_data() {
+        let canvas = Canvas::new(10, 2, Color::new(1., 0.8, 0.6));
+        let ppm = canvas.to_ppm();
+        let expected = indoc! {"255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204
+                                    153 255 204 153 255 204 153 255 204 153 255 204 153
+                                    255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204
+                                    153 255 204 153 255 204 153 255 204 153 255 204 153"};
+        let lines = &(ppm.split("\n").collect::<Vec<&str>>())[3..7].join("\n");
+        assert_eq!(expected, lines);
+    }
+
+    #[test]
+    fn it_terminates_the_ppm_with_a_newline_char() {
+        let canvas = Canvas::new(5, 3, Color::new(1., 0.8, 0.6));
+        let ppm = canvas.to_ppm();
+        assert!(ppm.ends_with("\n"));
+    }
+
+    #[test]
+    fn it_converts_to_an_rgb_image_like_the_ppm() {
+        let mut canvas = Canvas::new(2, 1, Color::new(1.5, 0.5, -0.5));
+        canvas.write_pixel(1, 0, Color::new(0., 0.2, 1.));
+        let image = canvas.to_rgb_image();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 128, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 51, 255]);
+    }
+
+    #[test]
+    fn it_keeps_unclamped_values_in_float_images() {
+        let image = Canvas::new(1, 2, Color::new(4., 0.5, -1.)).to_rgb32f_image();
+        assert_eq!(image.get_pixel(0, 1).0, [4., 0.5, -1.]);
+    }
+
+    #[test]
+    fn it_saves_in_the_format_of_the_extension() {
+        let canvas = Canvas::new(3, 2, Color::new(1., 0.8, 0.6));
+        let path = env::temp_dir().join(format!("canvas-{}.png", process::id()));
+        canvas.save(&path).unwrap();
+        assert_eq!(image::open(&path).unwrap().to_rgb8(), canvas.to_rgb_image());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_returns_an_error_for_unknown_extensions() {
+        let canvas = Canvas::new(1, 1, Color::black());
+        assert!(canvas.save(Path::new("canvas.unknown")).is_err());
+    }
+}
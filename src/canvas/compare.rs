@@ -0,0 +1,297 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use approx::AbsDiffEq;
+use std::env;
+use std::path::Path;
+
+// side of the square windows the structural similarity is averaged over
+const SSIM_WINDOW: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    // largest difference of a single channel
+    pub max_error: f64,
+    // mean absolute difference over every channel
+    pub mean_error: f64,
+    // in dB for a peak of 1, infinite for identical images
+    pub psnr: f64,
+    // structural similarity of the luminances, 1 for identical images
+    pub ssim: f64,
+    pub pixels_over_threshold: usize,
+    // the pixels over the threshold in red over a dimmed copy of the image
+    pub difference: Canvas,
+}
+
+impl Canvas {
+    // a pixel is over `threshold` when one of its channels differs by more
+    pub fn compare(&self, other: &Canvas, threshold: f64) -> Comparison {
+        assert!(self.width == other.width && self.height == other.height);
+        let mut difference = Canvas::new(self.width, self.height, Color::black());
+        let (mut max_error, mut total_error, mut squared_error) = (0_f64, 0., 0.);
+        let mut pixels_over_threshold = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (a, b) = (self.read_pixel(x, y), other.read_pixel(x, y));
+                let errors = [a.r - b.r, a.g - b.g, a.b - b.b].map(f64::abs);
+                let pixel_error = errors.iter().copied().fold(0., f64::max);
+                max_error = max_error.max(pixel_error);
+                total_error += errors.iter().sum::<f64>();
+                squared_error += errors.iter().map(|e| e * e).sum::<f64>();
+                if pixel_error > threshold {
+                    pixels_over_threshold += 1;
+                    difference.write_pixel(x, y, Color::red());
+                } else {
+                    let level = a.luminance().clamp(0., 1.) * 0.3;
+                    difference.write_pixel(x, y, Color::new(level, level, level));
+                }
+            }
+        }
+        let samples = (self.width * self.height * 3) as f64;
+        let mse = squared_error / samples;
+        Comparison {
+            max_error,
+            mean_error: total_error / samples,
+            psnr: 10. * (1. / mse).log10(),
+            ssim: self.ssim(other),
+            pixels_over_threshold,
+            difference,
+        }
+    }
+
+    // mean SSIM over half overlapping windows of the luminance
+    fn ssim(&self, other: &Canvas) -> f64 {
+        const C1: f64 = 0.01 * 0.01;
+        const C2: f64 = 0.03 * 0.03;
+        let window_width = SSIM_WINDOW.min(self.width);
+        let window_height = SSIM_WINDOW.min(self.height);
+        let starts = |size: usize, window: usize| (0..=size - window).step_by((window / 2).max(1));
+        let (mut total, mut windows) = (0., 0);
+        for top in starts(self.height, window_height) {
+            for left in starts(self.width, window_width) {
+                let pairs = (top..top + window_height)
+                    .flat_map(|y| (left..left + window_width).map(move |x| (x, y)))
+                    .map(|(x, y)| {
+                        (
+                            self.read_pixel(x, y).luminance(),
+                            other.read_pixel(x, y).luminance(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let n = pairs.len() as f64;
+                let mean_a = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+                let mean_b = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+                let (mut var_a, mut var_b, mut covariance) = (0., 0., 0.);
+                for (a, b) in &pairs {
+                    var_a += (a - mean_a) * (a - mean_a) / n;
+                    var_b += (b - mean_b) * (b - mean_b) / n;
+                    covariance += (a - mean_a) * (b - mean_b) / n;
+                }
+                total += ((2. * mean_a * mean_b + C1) * (2. * covariance + C2))
+                    / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+                windows += 1;
+            }
+        }
+        total / windows as f64
+    }
+}
+
+impl AbsDiffEq for Canvas {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> Self::Epsilon {
+        f64::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self
+                .pixels()
+                .iter()
+                .zip(other.pixels())
+                .all(|(a, b)| Color::abs_diff_eq(a, b, epsilon))
+    }
+}
+
+// golden image check for tests, no channel may differ by more than
+// `tolerance` from the reference image at `path`. The reference is written
+// instead when UPDATE_REFERENCES is set, and the highlighted differences are
+// saved next to it on failure.
+pub fn assert_matches_reference(canvas: &Canvas, path: &Path, tolerance: f64) {
+    check_reference(
+        canvas,
+        path,
+        tolerance,
+        env::var_os("UPDATE_REFERENCES").is_some(),
+    )
+}
+
+fn check_reference(canvas: &Canvas, path: &Path, tolerance: f64, update: bool) {
+    if update {
+        canvas.save(path).expect("Couldn’t write the reference");
+        return;
+    }
+    let reference = Canvas::load(path)
+        .unwrap_or_else(|e| panic!("Couldn’t load the reference {}: {}", path.display(), e));
+    assert!(
+        reference.width == canvas.width && reference.height == canvas.height,
+        "the reference is {}x{} and the canvas {}x{}",
+        reference.width,
+        reference.height,
+        canvas.width,
+        canvas.height
+    );
+    // compare what was saved, the reference only has 8 bits per channel
+    let saved = Canvas::from_rgb_image(&canvas.to_rgb_image());
+    if !saved.abs_diff_eq(&reference, tolerance) {
+        let comparison = saved.compare(&reference, tolerance);
+        let difference_path = path.with_extension("diff.png");
+        comparison.difference.save(&difference_path).ok();
+        panic!(
+            "{} pixels differ from {} by more than {} (max error {}, PSNR {:.1} dB, SSIM {:.4}), see {}",
+            comparison.pixels_over_threshold,
+            path.display(),
+            tolerance,
+            comparison.max_error,
+            comparison.psnr,
+            comparison.ssim,
+            difference_path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_reference;
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+    use std::path::PathBuf;
+    use std::{env, fs, process};
+
+    // a path of its own in the temporary directory, removed with the
+    // difference image next to it even when the test panics
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            Self(env::temp_dir().join(format!("{}-{}.png", name, process::id())))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            fs::remove_file(&self.0).ok();
+            fs::remove_file(self.0.with_extension("diff.png")).ok();
+        }
+    }
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height, Color::black());
+        for y in 0..height {
+            for x in 0..width {
+                let level = (x + y) as f64 / (width + height) as f64;
+                canvas.write_pixel(x, y, Color::new(level, 1. - level, 0.5));
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn it_finds_identical_canvases_identical() {
+        let canvas = gradient(16, 12);
+        let comparison = canvas.compare(&canvas.clone(), 0.);
+        assert_abs_diff_eq!(comparison.max_error, 0.);
+        assert_abs_diff_eq!(comparison.mean_error, 0.);
+        assert_eq!(comparison.psnr, f64::INFINITY);
+        assert_abs_diff_eq!(comparison.ssim, 1., epsilon = 1e-12);
+        assert_eq!(comparison.pixels_over_threshold, 0);
+    }
+
+    #[test]
+    fn it_measures_the_errors() {
+        let canvas = Canvas::new(4, 4, Color::new(0.5, 0.5, 0.5));
+        let mut other = canvas.clone();
+        other.write_pixel(1, 2, Color::new(0.9, 0.5, 0.5));
+        other.write_pixel(3, 3, Color::new(0.5, 0.55, 0.5));
+        let comparison = canvas.compare(&other, 0.1);
+        assert_abs_diff_eq!(comparison.max_error, 0.4, epsilon = 1e-12);
+        assert_abs_diff_eq!(comparison.mean_error, 0.45 / 48., epsilon = 1e-12);
+        let mse: f64 = (0.16 + 0.0025) / 48.;
+        assert_abs_diff_eq!(comparison.psnr, -10. * mse.log10(), epsilon = 1e-9);
+        assert_eq!(comparison.pixels_over_threshold, 1);
+        assert!(comparison.ssim < 1.);
+    }
+
+    #[test]
+    fn it_highlights_the_differences() {
+        let canvas = Canvas::new(3, 1, Color::white());
+        let mut other = canvas.clone();
+        other.write_pixel(2, 0, Color::black());
+        let difference = canvas.compare(&other, 0.01).difference;
+        assert_eq!(difference.read_pixel(2, 0), &Color::red());
+        assert_abs_diff_eq!(*difference.read_pixel(0, 0), Color::new(0.3, 0.3, 0.3));
+    }
+
+    #[test]
+    fn it_scores_structural_changes_lower_than_brightness_changes() {
+        let canvas = gradient(32, 32);
+        let mut brighter = canvas.clone();
+        let mut noisy = canvas.clone();
+        for y in 0..32 {
+            for x in 0..32 {
+                let color = *canvas.read_pixel(x, y);
+                brighter.write_pixel(x, y, color + Color::new(0.05, 0.05, 0.05));
+                let noise = if (x * 7 + y * 13) % 3 == 0 {
+                    0.05
+                } else {
+                    -0.05
+                };
+                noisy.write_pixel(x, y, color + Color::new(noise, noise, noise));
+            }
+        }
+        assert!(canvas.compare(&brighter, 0.).ssim > canvas.compare(&noisy, 0.).ssim);
+    }
+
+    #[test]
+    fn it_compares_canvases_with_a_tolerance() {
+        let canvas = gradient(5, 5);
+        let mut other = canvas.clone();
+        other.write_pixel(0, 0, *canvas.read_pixel(0, 0) + Color::new(0.001, 0., 0.));
+        assert_abs_diff_eq!(canvas, other, epsilon = 0.002);
+        assert_abs_diff_ne!(canvas, other, epsilon = 0.0005);
+        assert_abs_diff_ne!(canvas, gradient(5, 4), epsilon = 1.);
+    }
+
+    #[test]
+    fn it_matches_a_saved_reference() {
+        let path = TempPath::new("reference-matching");
+        let canvas = gradient(8, 8);
+        canvas.save(&path.0).unwrap();
+        check_reference(&canvas, &path.0, 1. / 255., false);
+    }
+
+    #[test]
+    fn it_writes_the_reference_when_updating() {
+        let path = TempPath::new("reference-updated");
+        check_reference(&gradient(4, 4), &path.0, 0., true);
+        assert!(path.0.exists());
+        check_reference(&gradient(4, 4), &path.0, 1. / 255., false);
+    }
+
+    #[test]
+    #[should_panic(expected = "pixels differ")]
+    fn it_fails_when_the_render_drifts_from_the_reference() {
+        let path = TempPath::new("reference-drifted");
+        gradient(8, 8).save(&path.0).unwrap();
+        let mut canvas = gradient(8, 8);
+        canvas.write_pixel(4, 4, Color::white());
+        check_reference(&canvas, &path.0, 1. / 255., false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn’t load the reference")]
+    fn it_fails_without_a_reference() {
+        let path = TempPath::new("reference-missing");
+        check_reference(&gradient(2, 2), &path.0, 0., false);
+    }
+}
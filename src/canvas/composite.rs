@@ -0,0 +1,302 @@
+use crate::canvas::{Canvas, ExrChannel, ExrPrecision, OutputTransform};
+use crate::color::{Color, Rgba};
+use image::{ImageFormat, ImageResult, Rgba as ImageRgba, RgbaImage};
+use std::path::Path;
+
+// Porter-Duff operators, the source is composited onto the destination
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Composite {
+    #[default]
+    Over,
+    // the source where the destination is
+    In,
+    // the source where the destination is not
+    Out,
+    // the source over the destination, only where the destination is
+    Atop,
+    // the source and the destination where they don't overlap
+    Xor,
+}
+
+impl Composite {
+    pub fn apply(&self, source: Rgba, destination: Rgba) -> Rgba {
+        // the fractions of the source and of the destination that remain
+        let (fs, fd) = match self {
+            Composite::Over => (1., 1. - source.alpha),
+            Composite::In => (destination.alpha, 0.),
+            Composite::Out => (1. - destination.alpha, 0.),
+            Composite::Atop => (destination.alpha, 1. - source.alpha),
+            Composite::Xor => (1. - destination.alpha, 1. - source.alpha),
+        };
+        source * fs + destination * fd
+    }
+}
+
+// separable blend modes, the blended color replaces the source where both
+// layers overlap and the result is composited over the destination
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Blend {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Add,
+}
+
+impl Blend {
+    fn channel(&self, backdrop: f64, source: f64) -> f64 {
+        match self {
+            Blend::Normal => source,
+            Blend::Multiply => backdrop * source,
+            Blend::Screen => backdrop + source - backdrop * source,
+            Blend::Overlay if backdrop <= 0.5 => 2. * backdrop * source,
+            Blend::Overlay => 1. - 2. * (1. - backdrop) * (1. - source),
+            Blend::Add => backdrop + source,
+        }
+    }
+
+    pub fn apply(&self, source: Rgba, destination: Rgba) -> Rgba {
+        let (cs, cb) = (source.straight(), destination.straight());
+        let blended = Color::new(
+            self.channel(cb.r, cs.r),
+            self.channel(cb.g, cs.g),
+            self.channel(cb.b, cs.b),
+        );
+        let both = source.alpha * destination.alpha;
+        Rgba {
+            color: source.color * (1. - destination.alpha)
+                + destination.color * (1. - source.alpha)
+                + blended * both,
+            alpha: source.alpha + destination.alpha - both,
+        }
+    }
+}
+
+impl Canvas<Rgba> {
+    pub fn transparent(width: usize, height: usize) -> Self {
+        Canvas::new(width, height, Rgba::transparent())
+    }
+
+    // `source` is composited onto self, both must have the same size
+    pub fn composite(&self, source: &Canvas<Rgba>, operator: Composite) -> Canvas<Rgba> {
+        self.combine(source, |s, d| operator.apply(s, d))
+    }
+
+    pub fn blend(&self, source: &Canvas<Rgba>, mode: Blend) -> Canvas<Rgba> {
+        self.combine(source, |s, d| mode.apply(s, d))
+    }
+
+    // drop the alpha by laying the canvas over an opaque background
+    pub fn flatten(&self, background: Color) -> Canvas {
+        let pixels = self
+            .pixels()
+            .iter()
+            .map(|pixel| pixel.color + background * (1. - pixel.alpha))
+            .collect();
+        Canvas::from_pixels(self.width, self.height, pixels)
+    }
+
+    // straight alpha as expected by png, the color goes through `output`
+    pub fn to_rgba_image_with(&self, output: &OutputTransform) -> RgbaImage {
+        RgbaImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let (x, y) = (x as usize, y as usize);
+            let pixel = self.read_pixel(x, y);
+            let [r, g, b] = output
+                .quantize(pixel.straight(), x, y, 255)
+                .map(|channel| channel as u8);
+            let alpha = output.quantize_value(pixel.alpha, x, y, 255) as u8;
+            ImageRgba([r, g, b, alpha])
+        })
+    }
+
+    pub fn to_rgba_image(&self) -> RgbaImage {
+        self.to_rgba_image_with(&OutputTransform::default())
+    }
+
+    pub fn save(&self, path: &Path) -> ImageResult<()> {
+        self.save_with(path, &OutputTransform::default())
+    }
+
+    pub fn save_with(&self, path: &Path, output: &OutputTransform) -> ImageResult<()> {
+        self.save_with_format(path, ImageFormat::from_path(path)?, output)
+    }
+
+    // EXR keeps the premultiplied color with an A channel, the formats
+    // without alpha get the canvas flattened onto black
+    pub fn save_with_format(
+        &self,
+        path: &Path,
+        format: ImageFormat,
+        output: &OutputTransform,
+    ) -> ImageResult<()> {
+        match format {
+            ImageFormat::OpenExr => {
+                let alpha = self.pixels().iter().map(|pixel| pixel.alpha).collect();
+                self.flatten(Color::black()).save_exr(
+                    path,
+                    ExrPrecision::Float,
+                    &[ExrChannel::new("A", alpha)],
+                )
+            }
+            ImageFormat::Hdr | ImageFormat::Jpeg | ImageFormat::Pnm => self
+                .flatten(Color::black())
+                .save_with_format(path, format, output),
+            _ => self
+                .to_rgba_image_with(output)
+                .save_with_format(path, format),
+        }
+    }
+
+    fn combine<F>(&self, source: &Canvas<Rgba>, operator: F) -> Canvas<Rgba>
+    where
+        F: Fn(Rgba, Rgba) -> Rgba,
+    {
+        assert!(self.width == source.width && self.height == source.height);
+        let pixels = source
+            .pixels()
+            .iter()
+            .zip(self.pixels())
+            .map(|(s, d)| operator(*s, *d))
+            .collect();
+        Canvas::from_pixels(self.width, self.height, pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blend, Composite};
+    use crate::canvas::Canvas;
+    use crate::color::{Color, Rgba};
+    use exr::prelude::read_first_flat_layer_from_file;
+    use std::{env, fs, process};
+
+    fn red() -> Rgba {
+        Rgba::new(Color::red(), 0.5)
+    }
+
+    fn blue() -> Rgba {
+        Rgba::new(Color::new(0., 0., 1.), 0.8)
+    }
+
+    #[test]
+    fn it_composites_with_the_porter_duff_operators() {
+        let (s, d) = (red(), blue());
+        let over = Composite::Over.apply(s, d);
+        assert_abs_diff_eq!(over.alpha, 0.9, epsilon = 1e-12);
+        assert_abs_diff_eq!(over.color, Color::new(0.5, 0., 0.4), epsilon = 1e-12);
+        let inside = Composite::In.apply(s, d);
+        assert_abs_diff_eq!(inside, Rgba::new(Color::red(), 0.4), epsilon = 1e-12);
+        let outside = Composite::Out.apply(s, d);
+        assert_abs_diff_eq!(outside, Rgba::new(Color::red(), 0.1), epsilon = 1e-12);
+        let atop = Composite::Atop.apply(s, d);
+        assert_abs_diff_eq!(atop.alpha, 0.8, epsilon = 1e-12);
+        assert_abs_diff_eq!(atop.color, Color::new(0.4, 0., 0.4), epsilon = 1e-12);
+        let xor = Composite::Xor.apply(s, d);
+        assert_abs_diff_eq!(xor.alpha, 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(xor.color, Color::new(0.1, 0., 0.4), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_leaves_the_destination_under_transparent_sources() {
+        for operator in [Composite::Over, Composite::Atop, Composite::Xor] {
+            assert_abs_diff_eq!(operator.apply(Rgba::transparent(), blue()), blue());
+        }
+        for mode in [Blend::Normal, Blend::Multiply, Blend::Screen, Blend::Add] {
+            assert_abs_diff_eq!(mode.apply(Rgba::transparent(), blue()), blue());
+        }
+    }
+
+    #[test]
+    fn it_blends_opaque_layers() {
+        let source = Rgba::opaque(Color::new(0.5, 0.2, 1.));
+        let backdrop = Rgba::opaque(Color::new(0.4, 0.8, 0.5));
+        let blend = |mode: Blend| mode.apply(source, backdrop).color;
+        assert_abs_diff_eq!(blend(Blend::Normal), Color::new(0.5, 0.2, 1.));
+        assert_abs_diff_eq!(
+            blend(Blend::Multiply),
+            Color::new(0.2, 0.16, 0.5),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            blend(Blend::Screen),
+            Color::new(0.7, 0.84, 1.),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            blend(Blend::Overlay),
+            Color::new(0.4, 0.68, 1.),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(blend(Blend::Add), Color::new(0.9, 1., 1.5), epsilon = 1e-12);
+        assert_abs_diff_eq!(Blend::Multiply.apply(source, backdrop).alpha, 1.);
+    }
+
+    #[test]
+    fn it_drops_renders_onto_backgrounds() {
+        let mut render = Canvas::transparent(2, 1);
+        render.write_pixel(0, 0, Rgba::opaque(Color::red()));
+        let background = Canvas::new(2, 1, Rgba::opaque(Color::white()));
+        let composited = background.composite(&render, Composite::Over);
+        assert_abs_diff_eq!(*composited.read_pixel(0, 0), Rgba::opaque(Color::red()));
+        assert_abs_diff_eq!(*composited.read_pixel(1, 0), Rgba::opaque(Color::white()));
+        let flat = render.flatten(Color::new(0., 1., 0.));
+        assert_eq!(flat.read_pixel(0, 0), &Color::red());
+        assert_eq!(flat.read_pixel(1, 0), &Color::new(0., 1., 0.));
+    }
+
+    #[test]
+    fn it_saves_the_transparency_to_png() {
+        let mut canvas = Canvas::transparent(2, 1);
+        canvas.write_pixel(1, 0, Rgba::new(Color::new(1., 0.5, 0.), 0.5));
+        let image = canvas.to_rgba_image();
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 128, 0, 128]);
+
+        let path = env::temp_dir().join(format!("rgba-{}.png", process::id()));
+        canvas.save(&path).unwrap();
+        assert_eq!(image::open(&path).unwrap().to_rgba8(), image);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_rounds_the_alpha_like_the_color() {
+        let canvas = Canvas::new(1, 1, Rgba::new(Color::new(0.251, 0.251, 0.251), 0.251));
+        let [r, g, b, alpha] = canvas.to_rgba_image().get_pixel(0, 0).0;
+        assert_eq!([r, g, b], [65; 3]);
+        assert_eq!(alpha, 65);
+    }
+
+    #[test]
+    fn it_saves_in_the_format_of_the_extension() {
+        let mut canvas = Canvas::transparent(2, 1);
+        canvas.write_pixel(1, 0, Rgba::new(Color::new(4., 0.5, 0.), 0.5));
+
+        let path = env::temp_dir().join(format!("rgba-{}.exr", process::id()));
+        canvas.save(&path).unwrap();
+        let image = read_first_flat_layer_from_file(&path).unwrap();
+        fs::remove_file(path).unwrap();
+        let channels = &image.layer_data.channel_data.list;
+        let names = channels
+            .iter()
+            .map(|channel| channel.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["A", "B", "G", "R"]);
+        let value = |channel: usize| {
+            channels[channel]
+                .sample_data
+                .value_by_flat_index(1)
+                .to_f32()
+        };
+        // premultiplied and unclamped
+        assert_eq!([value(0), value(3)], [0.5, 2.]);
+
+        for extension in ["jpg", "hdr", "ppm"] {
+            let path = env::temp_dir().join(format!("rgba-{}.{}", process::id(), extension));
+            canvas.save(&path).unwrap();
+            let saved = image::open(&path).unwrap().to_rgb8();
+            fs::remove_file(path).unwrap();
+            assert_eq!(saved.dimensions(), (2, 1));
+        }
+    }
+}
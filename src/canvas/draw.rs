@@ -0,0 +1,514 @@
+use crate::canvas::{Canvas, Pixel};
+
+// 5x7 glyphs for ASCII 32 to 126, one byte per column with the top row in
+// the lowest bit
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // #
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // )
+    [0x14, 0x08, 0x3e, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // 0
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // @
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // A
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // D
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // G
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // H
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // J
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // M
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // N
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // O
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // Q
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // T
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // U
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // V
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // f
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // g
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // j
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // l
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // q
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // t
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // u
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // v
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // y
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+
+// vertical subdivisions of a pixel row when filling polygons
+const POLYGON_SUBSAMPLES: usize = 4;
+
+// the drawing calls take pixel coordinates where pixel (x, y) covers the
+// square from (x, y) to (x + 1, y + 1), anything outside the canvas is clipped
+impl<P: Pixel> Canvas<P> {
+    // mix `color` into the pixel, `coverage` 1 replaces it
+    pub fn plot(&mut self, x: isize, y: isize, color: P, coverage: f64) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let coverage = coverage.clamp(0., 1.);
+        let (x, y) = (x as usize, y as usize);
+        let mixed = *self.read_pixel(x, y) * (1. - coverage) + color * coverage;
+        self.write_pixel(x, y, mixed);
+    }
+
+    // anti-aliased one pixel wide line, Xiaolin Wu's algorithm. The segment
+    // is clipped first so that the loop only walks the columns of the canvas,
+    // with a margin keeping the partly covered pixels of the cut ends outside.
+    pub fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), color: P) {
+        let margin = 2.;
+        let Some((from, to)) = clip_segment(
+            from,
+            to,
+            (-margin, -margin),
+            (self.width as f64 + margin, self.height as f64 + margin),
+        ) else {
+            return;
+        };
+        // work in pixel centers
+        let (mut x0, mut y0) = (from.0 - 0.5, from.1 - 0.5);
+        let (mut x1, mut y1) = (to.0 - 0.5, to.1 - 0.5);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            (x0, y0, x1, y1) = (y0, x0, y1, x1);
+        }
+        if x0 > x1 {
+            (x0, y0, x1, y1) = (x1, y1, x0, y0);
+        }
+        let gradient = if x1 == x0 { 1. } else { (y1 - y0) / (x1 - x0) };
+        let mut plot = |x: f64, y: f64, coverage: f64| {
+            let (x, y) = (x as isize, y as isize);
+            if steep {
+                self.plot(y, x, color, coverage)
+            } else {
+                self.plot(x, y, color, coverage)
+            }
+        };
+
+        let mut endpoint = |x: f64, y: f64| {
+            let x_end = x.round();
+            let y_end = y + gradient * (x_end - x);
+            let gap = if x == x0 {
+                1. - (x + 0.5).fract()
+            } else {
+                (x + 0.5).fract()
+            };
+            plot(x_end, y_end.floor(), (1. - y_end.fract()) * gap);
+            plot(x_end, y_end.floor() + 1., y_end.fract() * gap);
+            (x_end, y_end)
+        };
+        let (start, y_start) = endpoint(x0, y0);
+        let (end, _) = endpoint(x1, y1);
+
+        let mut y = y_start + gradient;
+        let mut x = start + 1.;
+        while x < end {
+            plot(x, y.floor(), 1. - y.fract());
+            plot(x, y.floor() + 1., y.fract());
+            y += gradient;
+            x += 1.;
+        }
+    }
+
+    // a stroke of any `width` with round caps
+    pub fn draw_thick_line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: P) {
+        let radius = width / 2.;
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length_squared = dx * dx + dy * dy;
+        self.fill_with(
+            (from.0.min(to.0) - radius, from.1.min(to.1) - radius),
+            (from.0.max(to.0) + radius, from.1.max(to.1) + radius),
+            color,
+            |px, py| {
+                // distance from the pixel center to the segment
+                let t = if length_squared == 0. {
+                    0.
+                } else {
+                    (((px - from.0) * dx + (py - from.1) * dy) / length_squared).clamp(0., 1.)
+                };
+                let (cx, cy) = (from.0 + t * dx - px, from.1 + t * dy - py);
+                radius + 0.5 - (cx * cx + cy * cy).sqrt()
+            },
+        );
+    }
+
+    pub fn draw_circle(&mut self, center: (f64, f64), radius: f64, color: P) {
+        self.fill_with(
+            (center.0 - radius - 1., center.1 - radius - 1.),
+            (center.0 + radius + 1., center.1 + radius + 1.),
+            color,
+            |px, py| {
+                let distance = ((px - center.0).powi(2) + (py - center.1).powi(2)).sqrt();
+                1. - (distance - radius).abs()
+            },
+        );
+    }
+
+    pub fn fill_circle(&mut self, center: (f64, f64), radius: f64, color: P) {
+        self.fill_with(
+            (center.0 - radius - 1., center.1 - radius - 1.),
+            (center.0 + radius + 1., center.1 + radius + 1.),
+            color,
+            |px, py| {
+                let distance = ((px - center.0).powi(2) + (py - center.1).powi(2)).sqrt();
+                radius + 0.5 - distance
+            },
+        );
+    }
+
+    // even-odd filling, the edges are anti-aliased by measuring how much of
+    // every pixel the spans of a few sub scanlines cover
+    pub fn fill_polygon(&mut self, points: &[(f64, f64)], color: P) {
+        if points.len() < 3 {
+            return;
+        }
+        let top = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let bottom = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        let first_row = top.floor().max(0.) as usize;
+        let last_row = (bottom.ceil().max(0.) as usize).min(self.height);
+        let mut coverage = vec![0.; self.width];
+        for row in first_row..last_row {
+            coverage.iter_mut().for_each(|c| *c = 0.);
+            for sub in 0..POLYGON_SUBSAMPLES {
+                let y = row as f64 + (sub as f64 + 0.5) / POLYGON_SUBSAMPLES as f64;
+                let mut crossings = points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .filter(|(a, b)| (a.1 <= y) != (b.1 <= y))
+                    .map(|(a, b)| a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0))
+                    .collect::<Vec<_>>();
+                crossings.sort_by(f64::total_cmp);
+                for span in crossings.chunks_exact(2) {
+                    let (start, end) = (span[0].max(0.), span[1].min(self.width as f64));
+                    if start >= end {
+                        continue;
+                    }
+                    let first = start.floor() as usize;
+                    let last = (end.ceil() as usize).min(self.width);
+                    for (x, c) in coverage.iter_mut().enumerate().take(last).skip(first) {
+                        let overlap = end.min(x as f64 + 1.) - start.max(x as f64);
+                        *c += overlap / POLYGON_SUBSAMPLES as f64;
+                    }
+                }
+            }
+            for (x, c) in coverage.iter().enumerate() {
+                if *c > 0. {
+                    self.plot(x as isize, row as isize, color, *c);
+                }
+            }
+        }
+    }
+
+    // the text starts with the top left corner of its first glyph at (x, y),
+    // `scale` makes every font pixel a square of that size
+    pub fn draw_text(&mut self, x: isize, y: isize, text: &str, scale: usize, color: P) {
+        let scale = scale.max(1) as isize;
+        let (mut pen_x, mut pen_y) = (x, y);
+        for character in text.chars() {
+            if character == '\n' {
+                pen_x = x;
+                pen_y += (GLYPH_HEIGHT as isize + 1) * scale;
+                continue;
+            }
+            let index = match character as u32 {
+                code @ 32..=126 => code as usize - 32,
+                _ => '?' as usize - 32,
+            };
+            for (column, bits) in FONT[index].iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if bits >> row & 1 == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            let px = pen_x + column as isize * scale + dx;
+                            let py = pen_y + row as isize * scale + dy;
+                            self.plot(px, py, color, 1.);
+                        }
+                    }
+                }
+            }
+            pen_x += (GLYPH_WIDTH as isize + 1) * scale;
+        }
+    }
+
+    // plot the pixels of a box with the coverage given at their centers
+    fn fill_with<F>(&mut self, min: (f64, f64), max: (f64, f64), color: P, coverage: F)
+    where
+        F: Fn(f64, f64) -> f64,
+    {
+        let clip = |value: f64, size: usize| value.clamp(0., size as f64) as usize;
+        for y in clip(min.1.floor(), self.height)..clip(max.1.ceil(), self.height) {
+            for x in clip(min.0.floor(), self.width)..clip(max.0.ceil(), self.width) {
+                let c = coverage(x as f64 + 0.5, y as f64 + 0.5);
+                if c > 0. {
+                    self.plot(x as isize, y as isize, color, c);
+                }
+            }
+        }
+    }
+}
+
+// the part of the segment inside the box from `min` to `max`, Liang-Barsky.
+// None when it misses the box, or when an end isn't finite and there's no
+// direction to clip along.
+fn clip_segment(
+    from: (f64, f64),
+    to: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    if !dx.is_finite() || !dy.is_finite() {
+        return None;
+    }
+    let (mut enter, mut exit) = (0_f64, 1_f64);
+    // the distances to the left, right, top and bottom edges along the
+    // segment, for the ends where it enters and leaves
+    for (p, q) in [
+        (-dx, from.0 - min.0),
+        (dx, max.0 - from.0),
+        (-dy, from.1 - min.1),
+        (dy, max.1 - from.1),
+    ] {
+        if p == 0. {
+            // parallel to the edge, and outside of it
+            if q < 0. {
+                return None;
+            }
+        } else if p < 0. {
+            enter = enter.max(q / p);
+        } else {
+            exit = exit.min(q / p);
+        }
+    }
+    (enter <= exit).then_some((
+        (from.0 + enter * dx, from.1 + enter * dy),
+        (from.0 + exit * dx, from.1 + exit * dy),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::Canvas;
+    use crate::color::{Color, Rgba};
+
+    fn coverage(canvas: &Canvas) -> f64 {
+        canvas.pixels().iter().map(|c| c.r).sum()
+    }
+
+    #[test]
+    fn it_clips_drawing_outside_of_the_canvas() {
+        let mut canvas = Canvas::new(4, 4, Color::black());
+        canvas.plot(-1, 2, Color::white(), 1.);
+        canvas.plot(2, 9, Color::white(), 1.);
+        canvas.draw_line((-10., -5.), (20., 30.), Color::white());
+        canvas.fill_circle((100., 100.), 5., Color::white());
+        canvas.draw_text(-3, -3, "clipped", 2, Color::white());
+        canvas.fill_polygon(&[(-5., -5.), (50., -5.), (-5., 50.)], Color::white());
+        assert!(coverage(&canvas) > 0.);
+    }
+
+    #[test]
+    fn it_draws_straight_lines_at_full_intensity() {
+        let mut canvas = Canvas::new(6, 3, Color::black());
+        canvas.draw_line((0., 1.5), (6., 1.5), Color::white());
+        for x in 0..6 {
+            assert_abs_diff_eq!(canvas.read_pixel(x, 1).r, 1., epsilon = 1e-12);
+            assert_abs_diff_eq!(canvas.read_pixel(x, 0).r, 0.);
+        }
+    }
+
+    #[test]
+    fn it_clips_lines_to_far_off_points_before_drawing_them() {
+        let mut canvas = Canvas::new(6, 3, Color::black());
+        canvas.draw_line((0., 1.5), (1e12, 1.5), Color::white());
+        for x in 0..6 {
+            assert_abs_diff_eq!(canvas.read_pixel(x, 1).r, 1., epsilon = 1e-12);
+        }
+        // the same pixels as the line ending just past the canvas
+        let mut clipped = Canvas::new(6, 3, Color::black());
+        clipped.draw_line((-1e12, -1e12), (3., 3.), Color::white());
+        let mut short = Canvas::new(6, 3, Color::black());
+        short.draw_line((-3., -3.), (3., 3.), Color::white());
+        assert_eq!(clipped, short);
+        canvas.draw_line((0., 0.), (f64::INFINITY, 2.), Color::white());
+        canvas.draw_line((f64::NAN, 0.), (3., 2.), Color::white());
+        canvas.draw_line((-1e12, 10.), (1e12, 10.), Color::white());
+        assert_abs_diff_eq!(coverage(&canvas), 6., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn it_spreads_diagonal_lines_over_two_pixels() {
+        let mut canvas = Canvas::new(8, 8, Color::black());
+        canvas.draw_line((0.5, 1.), (7.5, 4.5), Color::white());
+        // every column gets a total intensity of one
+        for x in 1..7 {
+            let column = (0..8).map(|y| canvas.read_pixel(x, y).r).sum::<f64>();
+            assert_abs_diff_eq!(column, 1., epsilon = 1e-9);
+        }
+        let partial = canvas
+            .pixels()
+            .iter()
+            .filter(|c| c.r > 0. && c.r < 1.)
+            .count();
+        assert!(partial > 0);
+    }
+
+    #[test]
+    fn it_fills_circles_with_their_area() {
+        let mut canvas = Canvas::new(40, 40, Color::black());
+        canvas.fill_circle((20., 20.), 10., Color::white());
+        assert_abs_diff_eq!(coverage(&canvas), 100. * std::f64::consts::PI, epsilon = 2.);
+        assert_eq!(canvas.read_pixel(20, 20), &Color::white());
+        assert_eq!(canvas.read_pixel(20, 5), &Color::black());
+    }
+
+    #[test]
+    fn it_draws_circle_outlines() {
+        let mut canvas = Canvas::new(40, 40, Color::black());
+        canvas.draw_circle((20., 20.), 10., Color::white());
+        assert_eq!(canvas.read_pixel(20, 20), &Color::black());
+        assert!(canvas.read_pixel(29, 20).r > 0.4);
+        // about one pixel of ink along the circumference
+        assert_abs_diff_eq!(coverage(&canvas), 20. * std::f64::consts::PI, epsilon = 3.);
+    }
+
+    #[test]
+    fn it_fills_polygons_with_their_area() {
+        let mut canvas = Canvas::new(10, 10, Color::black());
+        canvas.fill_polygon(&[(1., 1.), (9., 1.), (1., 9.)], Color::white());
+        assert_abs_diff_eq!(coverage(&canvas), 32., epsilon = 1e-9);
+        assert_eq!(canvas.read_pixel(2, 2), &Color::white());
+        assert_eq!(canvas.read_pixel(8, 8), &Color::black());
+    }
+
+    #[test]
+    fn it_leaves_the_holes_of_polygons_empty() {
+        let mut canvas = Canvas::new(10, 10, Color::black());
+        // a square with a square hole, as a single outline going back and forth
+        let outline = [
+            (0., 0.),
+            (10., 0.),
+            (10., 10.),
+            (0., 10.),
+            (0., 0.),
+            (3., 3.),
+            (3., 7.),
+            (7., 7.),
+            (7., 3.),
+            (3., 3.),
+        ];
+        canvas.fill_polygon(&outline, Color::white());
+        assert_eq!(canvas.read_pixel(5, 5), &Color::black());
+        assert_eq!(canvas.read_pixel(1, 5), &Color::white());
+        assert_abs_diff_eq!(coverage(&canvas), 84., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn it_draws_thick_lines() {
+        let mut canvas = Canvas::new(20, 20, Color::black());
+        canvas.draw_thick_line((5., 10.), (15., 10.), 4., Color::white());
+        assert_eq!(canvas.read_pixel(10, 9), &Color::white());
+        assert_eq!(canvas.read_pixel(10, 11), &Color::white());
+        assert_eq!(canvas.read_pixel(10, 14), &Color::black());
+        // a 10x4 rectangle plus two half discs of radius 2
+        assert_abs_diff_eq!(
+            coverage(&canvas),
+            40. + 4. * std::f64::consts::PI,
+            epsilon = 2.
+        );
+    }
+
+    #[test]
+    fn it_writes_text_with_the_bitmap_font() {
+        let mut canvas = Canvas::new(12, 8, Color::black());
+        canvas.draw_text(0, 0, "T1", 1, Color::white());
+        // the bar of the T
+        for x in 0..5 {
+            assert_eq!(canvas.read_pixel(x, 0), &Color::white());
+        }
+        assert_eq!(canvas.read_pixel(2, 6), &Color::white());
+        assert_eq!(canvas.read_pixel(0, 6), &Color::black());
+        // the foot of the 1, after the one pixel gap
+        for x in 7..10 {
+            assert_eq!(canvas.read_pixel(x, 6), &Color::white());
+        }
+    }
+
+    #[test]
+    fn it_scales_text_and_draws_on_rgba_canvases() {
+        let mut canvas = Canvas::transparent(20, 20);
+        canvas.draw_text(0, 0, "I", 2, Rgba::opaque(Color::white()));
+        assert_eq!(canvas.read_pixel(5, 13), &Rgba::opaque(Color::white()));
+        assert_eq!(canvas.read_pixel(0, 0), &Rgba::transparent());
+    }
+}
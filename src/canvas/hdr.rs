@@ -0,0 +1,190 @@
+use crate::canvas::Canvas;
+use exr::prelude::{
+    f16, AnyChannel, AnyChannels, Encoding, FlatSamples, Image, Layer, LayerAttributes, SmallVec,
+    WritableImage,
+};
+use image::codecs::hdr::HdrEncoder;
+use image::error::{EncodingError, ImageFormatHint, ParameterError, ParameterErrorKind};
+use image::{ImageError, ImageFormat, ImageResult, Rgb};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum ExrPrecision {
+    #[default]
+    Half,
+    Float,
+}
+
+// an extra channel stored next to R, G and B, e.g. a depth pass named "Z"
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExrChannel {
+    pub name: String,
+    // row major, one value per pixel
+    pub values: Vec<f64>,
+}
+
+impl ExrChannel {
+    pub fn new(name: &str, values: Vec<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            values,
+        }
+    }
+}
+
+impl ExrPrecision {
+    fn samples(&self, values: impl Iterator<Item = f64>) -> FlatSamples {
+        match self {
+            ExrPrecision::Half => FlatSamples::F16(values.map(f16::from_f64).collect()),
+            ExrPrecision::Float => FlatSamples::F32(values.map(|v| v as f32).collect()),
+        }
+    }
+}
+
+impl Canvas {
+    // Radiance RGBE, highlights above 1 are kept with a shared exponent
+    pub fn write_hdr<W: Write>(&self, writer: W) -> ImageResult<()> {
+        let pixels = self
+            .pixels()
+            .iter()
+            .map(|color| Rgb([color.r as f32, color.g as f32, color.b as f32]))
+            .collect::<Vec<_>>();
+        HdrEncoder::new(writer).encode(&pixels, self.width, self.height)
+    }
+
+    pub fn save_hdr(&self, path: &Path) -> ImageResult<()> {
+        self.write_hdr(BufWriter::new(File::create(path)?))
+    }
+
+    // OpenEXR with the color in R, G and B followed by the `extra` channels
+    pub fn save_exr(
+        &self,
+        path: &Path,
+        precision: ExrPrecision,
+        extra: &[ExrChannel],
+    ) -> ImageResult<()> {
+        let pixels = self.pixels();
+        for (i, channel) in extra.iter().enumerate() {
+            if channel.values.len() != pixels.len() {
+                return Err(ImageError::Parameter(ParameterError::from_kind(
+                    ParameterErrorKind::DimensionMismatch,
+                )));
+            }
+            if ["R", "G", "B"].contains(&channel.name.as_str())
+                || extra[..i].iter().any(|other| other.name == channel.name)
+            {
+                return Err(ImageError::Parameter(ParameterError::from_kind(
+                    ParameterErrorKind::Generic(format!("duplicate channel {}", channel.name)),
+                )));
+            }
+        }
+        let mut channels = vec![
+            AnyChannel::new("R", precision.samples(pixels.iter().map(|c| c.r))),
+            AnyChannel::new("G", precision.samples(pixels.iter().map(|c| c.g))),
+            AnyChannel::new("B", precision.samples(pixels.iter().map(|c| c.b))),
+        ];
+        for channel in extra {
+            channels.push(AnyChannel::new(
+                channel.name.as_str(),
+                precision.samples(channel.values.iter().copied()),
+            ));
+        }
+        let layer = Layer::new(
+            (self.width, self.height),
+            LayerAttributes::default(),
+            Encoding::SMALL_LOSSLESS,
+            AnyChannels::sort(SmallVec::from_vec(channels)),
+        );
+        Image::from_layer(layer)
+            .write()
+            .to_file(path)
+            .map_err(|e| match e {
+                exr::error::Error::Io(e) => ImageError::IoError(e),
+                e => ImageError::Encoding(EncodingError::new(
+                    ImageFormatHint::Exact(ImageFormat::OpenExr),
+                    e,
+                )),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExrChannel, ExrPrecision};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+    use exr::prelude::{read_first_flat_layer_from_file, FlatSamples};
+    use image::codecs::hdr::HdrDecoder;
+    use image::error::{ImageError, ParameterErrorKind};
+    use std::{env, fs, process};
+
+    fn canvas() -> Canvas {
+        let mut canvas = Canvas::new(2, 2, Color::new(0.25, 0.5, 1.));
+        canvas.write_pixel(1, 0, Color::new(16., 4., 0.5));
+        canvas
+    }
+
+    #[test]
+    fn it_keeps_highlights_in_radiance_hdr() {
+        let mut data = vec![];
+        canvas().write_hdr(&mut data).unwrap();
+        assert!(data.starts_with(b"#?RADIANCE"));
+        let pixels = HdrDecoder::new(data.as_slice())
+            .unwrap()
+            .read_image_hdr()
+            .unwrap();
+        assert_eq!(pixels[1].0, [16., 4., 0.5]);
+        assert_eq!(pixels[2].0, [0.25, 0.5, 1.]);
+    }
+
+    #[test]
+    fn it_writes_exr_with_extra_channels() {
+        for (precision, name) in [(ExrPrecision::Half, "half"), (ExrPrecision::Float, "float")] {
+            let path = env::temp_dir().join(format!("canvas-{}-{}.exr", name, process::id()));
+            let depth = ExrChannel::new("Z", vec![1., 2., 3., 1000.]);
+            canvas().save_exr(&path, precision, &[depth]).unwrap();
+
+            let image = read_first_flat_layer_from_file(&path).unwrap();
+            fs::remove_file(path).unwrap();
+            let channels = &image.layer_data.channel_data.list;
+            let names = channels
+                .iter()
+                .map(|channel| channel.name.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(names, vec!["B", "G", "R", "Z"]);
+            match (&channels[2].sample_data, precision) {
+                (FlatSamples::F16(red), ExrPrecision::Half) => {
+                    assert_eq!(red[1].to_f64(), 16.)
+                }
+                (FlatSamples::F32(red), ExrPrecision::Float) => assert_eq!(red[1], 16.),
+                _ => panic!("unexpected sample type"),
+            }
+            assert_abs_diff_eq!(
+                channels[3].sample_data.value_by_flat_index(3).to_f32(),
+                1000.
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_extra_channels() {
+        let path = env::temp_dir().join(format!("canvas-invalid-{}.exr", process::id()));
+        let short = ExrChannel::new("Z", vec![1., 2.]);
+        let Err(ImageError::Parameter(error)) =
+            canvas().save_exr(&path, ExrPrecision::Half, &[short])
+        else {
+            panic!("the channel is too short");
+        };
+        assert_eq!(error.kind(), ParameterErrorKind::DimensionMismatch);
+        for names in [["G", "Z"], ["Z", "Z"]] {
+            let extra = names.map(|name| ExrChannel::new(name, vec![0.; 4]));
+            assert!(matches!(
+                canvas().save_exr(&path, ExrPrecision::Half, &extra),
+                Err(ImageError::Parameter(_))
+            ));
+        }
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,16 @@
+mod base;
+mod compare;
+mod composite;
+mod draw;
+mod hdr;
+mod ops;
+mod ppm;
+mod tone;
+
+pub use base::{Canvas, Pixel};
+pub use compare::{assert_matches_reference, Comparison};
+pub use composite::{Blend, Composite};
+pub use hdr::{ExrChannel, ExrPrecision};
+pub use ops::{Kernel, Resize};
+pub use ppm::PpmError;
+pub use tone::{Dither, OutputTransform, ToneMap, Transfer};
@@ -0,0 +1,338 @@
+use crate::canvas::{Canvas, Pixel};
+use std::f64::consts::PI;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Resize {
+    Nearest,
+    #[default]
+    Bilinear,
+    // windowed sinc over 3 lobes, sharper but may ring around edges
+    Lanczos3,
+}
+
+impl Resize {
+    fn support(&self) -> f64 {
+        match self {
+            Resize::Nearest => 0.5,
+            Resize::Bilinear => 1.,
+            Resize::Lanczos3 => 3.,
+        }
+    }
+
+    fn weight(&self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            Resize::Nearest if x < 0.5 => 1.,
+            Resize::Bilinear if x < 1. => 1. - x,
+            Resize::Lanczos3 if x < 1e-8 => 1.,
+            Resize::Lanczos3 if x < 3. => {
+                let px = PI * x;
+                3. * px.sin() * (px / 3.).sin() / (px * px)
+            }
+            _ => 0.,
+        }
+    }
+
+    // the normalized source contributions to every destination pixel along
+    // one axis, as the first source index and its weights
+    fn contributions(&self, source: usize, destination: usize) -> Vec<(usize, Vec<f64>)> {
+        let scale = source as f64 / destination as f64;
+        // widen the filter when shrinking so that every source pixel counts
+        let stretch = scale.max(1.);
+        let support = self.support() * stretch;
+        (0..destination)
+            .map(|i| {
+                let center = (i as f64 + 0.5) * scale;
+                if *self == Resize::Nearest {
+                    return ((center as usize).min(source - 1), vec![1.]);
+                }
+                let start = (center - support).floor().max(0.) as usize;
+                let end = ((center + support).ceil() as usize).min(source);
+                let mut weights = (start..end)
+                    .map(|j| self.weight((j as f64 + 0.5 - center) / stretch))
+                    .collect::<Vec<_>>();
+                let total = weights.iter().sum::<f64>();
+                weights.iter_mut().for_each(|w| *w /= total);
+                (start, weights)
+            })
+            .collect()
+    }
+}
+
+// a convolution matrix, applied centered on every pixel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    pub width: usize,
+    pub height: usize,
+    // row major
+    pub weights: Vec<f64>,
+}
+
+impl Kernel {
+    pub fn new(width: usize, height: usize, weights: Vec<f64>) -> Self {
+        assert!(width % 2 == 1 && height % 2 == 1, "kernels need a center");
+        assert_eq!(weights.len(), width * height);
+        Self {
+            width,
+            height,
+            weights,
+        }
+    }
+
+    pub fn box_blur(radius: usize) -> Self {
+        let size = 2 * radius + 1;
+        Self::new(size, size, vec![1. / (size * size) as f64; size * size])
+    }
+
+    pub fn gaussian_blur(sigma: f64) -> Self {
+        let radius = (3. * sigma).ceil() as isize;
+        let size = (2 * radius + 1) as usize;
+        let mut weights = (-radius..=radius)
+            .flat_map(|y| (-radius..=radius).map(move |x| (x, y)))
+            .map(|(x, y)| (-((x * x + y * y) as f64) / (2. * sigma * sigma)).exp())
+            .collect::<Vec<_>>();
+        let total = weights.iter().sum::<f64>();
+        weights.iter_mut().for_each(|w| *w /= total);
+        Self::new(size, size, weights)
+    }
+
+    pub fn sharpen() -> Self {
+        Self::new(3, 3, vec![0., -1., 0., -1., 5., -1., 0., -1., 0.])
+    }
+
+    // laplacian, flat areas become black
+    pub fn edge_detect() -> Self {
+        Self::new(3, 3, vec![-1., -1., -1., -1., 8., -1., -1., -1., -1.])
+    }
+}
+
+impl<P: Copy> Canvas<P> {
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Canvas<P> {
+        assert!(x + width <= self.width && y + height <= self.height);
+        let pixels = (y..y + height)
+            .flat_map(|row| (x..x + width).map(move |col| (col, row)))
+            .map(|(col, row)| *self.read_pixel(col, row))
+            .collect();
+        Canvas::from_pixels(width, height, pixels)
+    }
+
+    // copy `source` with its top left corner at (x, y), clipped to the canvas
+    pub fn blit(&mut self, source: &Canvas<P>, x: isize, y: isize) {
+        for row in 0..source.height {
+            for col in 0..source.width {
+                let (tx, ty) = (x + col as isize, y + row as isize);
+                if tx >= 0 && ty >= 0 && (tx as usize) < self.width && (ty as usize) < self.height {
+                    self.write_pixel(tx as usize, ty as usize, *source.read_pixel(col, row));
+                }
+            }
+        }
+    }
+
+    // upside down, handy to draw with the y axis pointing up
+    pub fn flip_vertical(&self) -> Canvas<P> {
+        self.remap(self.width, self.height, |x, y| (x, self.height - 1 - y))
+    }
+
+    pub fn flip_horizontal(&self) -> Canvas<P> {
+        self.remap(self.width, self.height, |x, y| (self.width - 1 - x, y))
+    }
+
+    // a quarter turn clockwise
+    pub fn rotate_90(&self) -> Canvas<P> {
+        self.remap(self.height, self.width, |x, y| (y, self.height - 1 - x))
+    }
+
+    // a quarter turn counterclockwise
+    pub fn rotate_270(&self) -> Canvas<P> {
+        self.remap(self.height, self.width, |x, y| (self.width - 1 - y, x))
+    }
+
+    // build a canvas where every pixel is read from `source(x, y)` in self
+    fn remap<F>(&self, width: usize, height: usize, source: F) -> Canvas<P>
+    where
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (sx, sy) = source(x, y);
+                *self.read_pixel(sx, sy)
+            })
+            .collect();
+        Canvas::from_pixels(width, height, pixels)
+    }
+}
+
+impl<P: Pixel> Canvas<P> {
+    pub fn resize(&self, width: usize, height: usize, filter: Resize) -> Canvas<P> {
+        assert!(width > 0 && height > 0);
+        // separable, rows first then columns
+        let columns = filter.contributions(self.width, width);
+        let mut horizontal = Canvas::new(width, self.height, P::zero());
+        for y in 0..self.height {
+            for (x, (start, weights)) in columns.iter().enumerate() {
+                let color = weights.iter().enumerate().fold(P::zero(), |sum, (i, w)| {
+                    sum + *self.read_pixel(start + i, y) * *w
+                });
+                horizontal.write_pixel(x, y, color);
+            }
+        }
+        let rows = filter.contributions(self.height, height);
+        let mut resized = Canvas::new(width, height, P::zero());
+        for (y, (start, weights)) in rows.iter().enumerate() {
+            for x in 0..width {
+                let color = weights.iter().enumerate().fold(P::zero(), |sum, (i, w)| {
+                    sum + *horizontal.read_pixel(x, start + i) * *w
+                });
+                resized.write_pixel(x, y, color);
+            }
+        }
+        resized
+    }
+
+    // the edge pixels are repeated where the kernel goes past the canvas
+    pub fn convolve(&self, kernel: &Kernel) -> Canvas<P> {
+        let (half_width, half_height) = (kernel.width as isize / 2, kernel.height as isize / 2);
+        let clamp = |value: isize, size: usize| value.clamp(0, size as isize - 1) as usize;
+        self.remap_colors(|x, y| {
+            let mut sum = P::zero();
+            for ky in 0..kernel.height {
+                for kx in 0..kernel.width {
+                    let sx = clamp(x as isize + kx as isize - half_width, self.width);
+                    let sy = clamp(y as isize + ky as isize - half_height, self.height);
+                    sum = sum + *self.read_pixel(sx, sy) * kernel.weights[ky * kernel.width + kx];
+                }
+            }
+            sum
+        })
+    }
+
+    fn remap_colors<F>(&self, color: F) -> Canvas<P>
+    where
+        F: Fn(usize, usize) -> P,
+    {
+        let pixels = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| color(x, y))
+            .collect();
+        Canvas::from_pixels(self.width, self.height, pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kernel, Resize};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+
+    // every pixel has a distinct color made of its coordinates
+    fn numbered(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height, Color::black());
+        for y in 0..height {
+            for x in 0..width {
+                canvas.write_pixel(x, y, Color::new(x as f64, y as f64, 0.));
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn it_crops_canvases() {
+        let cropped = numbered(5, 4).crop(1, 2, 3, 2);
+        assert_eq!((cropped.width, cropped.height), (3, 2));
+        assert_eq!(cropped.read_pixel(0, 0), &Color::new(1., 2., 0.));
+        assert_eq!(cropped.read_pixel(2, 1), &Color::new(3., 3., 0.));
+    }
+
+    #[test]
+    fn it_flips_canvases() {
+        let canvas = numbered(3, 2);
+        let vertical = canvas.flip_vertical();
+        assert_eq!(vertical.read_pixel(0, 0), &Color::new(0., 1., 0.));
+        assert_eq!(vertical.read_pixel(2, 1), &Color::new(2., 0., 0.));
+        let horizontal = canvas.flip_horizontal();
+        assert_eq!(horizontal.read_pixel(0, 0), &Color::new(2., 0., 0.));
+        assert_eq!(canvas.flip_vertical().flip_vertical(), canvas);
+    }
+
+    #[test]
+    fn it_rotates_canvases_a_quarter_turn() {
+        let canvas = numbered(3, 2);
+        let rotated = canvas.rotate_90();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        // the bottom left corner becomes the top left one
+        assert_eq!(rotated.read_pixel(0, 0), &Color::new(0., 1., 0.));
+        assert_eq!(rotated.read_pixel(1, 0), &Color::new(0., 0., 0.));
+        assert_eq!(rotated.read_pixel(1, 2), &Color::new(2., 0., 0.));
+        assert_eq!(rotated.rotate_270(), canvas);
+        assert_eq!(
+            canvas.rotate_90().rotate_90(),
+            canvas.flip_vertical().flip_horizontal()
+        );
+    }
+
+    #[test]
+    fn it_blits_canvases_clipped_to_the_destination() {
+        let mut canvas: Canvas = Canvas::new(4, 3, Color::black());
+        canvas.blit(&Canvas::new(2, 2, Color::white()), 3, -1);
+        assert_eq!(canvas.read_pixel(3, 0), &Color::white());
+        assert_eq!(canvas.read_pixel(3, 1), &Color::black());
+        assert_eq!(canvas.read_pixel(2, 0), &Color::black());
+    }
+
+    #[test]
+    fn it_resizes_with_the_nearest_pixel() {
+        let resized = numbered(2, 2).resize(4, 4, Resize::Nearest);
+        assert_eq!(resized.read_pixel(1, 1), &Color::new(0., 0., 0.));
+        assert_eq!(resized.read_pixel(2, 3), &Color::new(1., 1., 0.));
+        let shrunk = numbered(4, 4).resize(2, 2, Resize::Nearest);
+        assert_eq!(shrunk.read_pixel(1, 1), &Color::new(3., 3., 0.));
+    }
+
+    #[test]
+    fn it_keeps_flat_colors_when_resizing() {
+        let canvas = Canvas::new(7, 5, Color::new(0.2, 0.4, 0.6));
+        for filter in [Resize::Nearest, Resize::Bilinear, Resize::Lanczos3] {
+            for (width, height) in [(3, 2), (16, 11)] {
+                let resized = canvas.resize(width, height, filter);
+                for color in resized.pixels() {
+                    assert_abs_diff_eq!(*color, Color::new(0.2, 0.4, 0.6), epsilon = 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_interpolates_linear_ramps_when_resizing() {
+        let resized = numbered(4, 1).resize(8, 1, Resize::Bilinear);
+        // the centers of the new pixels fall between the old ones
+        assert_abs_diff_eq!(resized.read_pixel(3, 0).r, 1.25);
+        assert_abs_diff_eq!(resized.read_pixel(4, 0).r, 1.75);
+        let halved = numbered(4, 1).resize(2, 1, Resize::Lanczos3);
+        assert!(halved.read_pixel(0, 0).r < halved.read_pixel(1, 0).r);
+    }
+
+    #[test]
+    fn it_blurs_without_changing_flat_areas() {
+        let canvas = Canvas::new(5, 5, Color::new(0.5, 0.5, 0.5));
+        for kernel in [Kernel::box_blur(1), Kernel::gaussian_blur(1.)] {
+            assert_abs_diff_eq!(canvas.convolve(&kernel), canvas, epsilon = 1e-12);
+        }
+        let mut dot = Canvas::new(3, 3, Color::black());
+        dot.write_pixel(1, 1, Color::new(9., 9., 9.));
+        let blurred = dot.convolve(&Kernel::box_blur(1));
+        assert_abs_diff_eq!(*blurred.read_pixel(0, 0), Color::new(1., 1., 1.));
+    }
+
+    #[test]
+    fn it_detects_edges_and_sharpens() {
+        let mut canvas = Canvas::new(4, 3, Color::black());
+        canvas.blit(&Canvas::new(2, 3, Color::white()), 2, 0);
+        let edges = canvas.convolve(&Kernel::edge_detect());
+        assert_abs_diff_eq!(*edges.read_pixel(0, 1), Color::black());
+        assert_abs_diff_eq!(*edges.read_pixel(2, 1), Color::new(3., 3., 3.));
+        let sharpened = canvas.convolve(&Kernel::sharpen());
+        assert_abs_diff_eq!(*sharpened.read_pixel(2, 1), Color::new(2., 2., 2.));
+        assert_abs_diff_eq!(*sharpened.read_pixel(1, 1), Color::new(-1., -1., -1.));
+    }
+}
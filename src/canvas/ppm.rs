@@ -0,0 +1,303 @@
+use crate::canvas::{Canvas, OutputTransform};
+use crate::color::Color;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PpmError {
+    UnsupportedFormat(String),
+    InvalidHeader(String),
+    InvalidData(String),
+    UnexpectedEnd,
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PpmError::UnsupportedFormat(magic) => {
+                write!(f, "unsupported netpbm format {}", magic)
+            }
+            PpmError::InvalidHeader(token) => write!(f, "invalid header value {}", token),
+            PpmError::InvalidData(message) => write!(f, "invalid pixel data, {}", message),
+            PpmError::UnexpectedEnd => write!(f, "the pixel data ends too early"),
+        }
+    }
+}
+
+impl Error for PpmError {}
+
+// scale a channel to 0..=maxval, rounding up like the ascii ppm output
+pub(super) fn quantize(channel: f64, maxval: u16) -> u16 {
+    (channel * maxval as f64).ceil().clamp(0., maxval as f64) as u16
+}
+
+fn push_sample(data: &mut Vec<u8>, value: u16, maxval: u16) {
+    // samples are one byte up to 255 and two big endian bytes above
+    if maxval < 256 {
+        data.push(value as u8);
+    } else {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+impl Canvas {
+    // binary P6, `maxval` up to 65535 for 16 bits per channel
+    pub fn to_ppm_binary(&self, maxval: u16) -> Vec<u8> {
+        self.to_ppm_binary_with(maxval, &OutputTransform::default())
+    }
+
+    pub fn to_ppm_binary_with(&self, maxval: u16, output: &OutputTransform) -> Vec<u8> {
+        assert!(maxval > 0);
+        let mut data = format!("P6\n{} {}\n{}\n", self.width, self.height, maxval).into_bytes();
+        for (i, color) in self.pixels().iter().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            for channel in output.quantize(*color, x, y, maxval) {
+                push_sample(&mut data, channel, maxval);
+            }
+        }
+        data
+    }
+
+    // binary P5 grayscale from the luminance of every pixel
+    pub fn to_pgm(&self, maxval: u16) -> Vec<u8> {
+        self.to_pgm_with(maxval, &OutputTransform::default())
+    }
+
+    // the luminance is taken on the linear values, before `output`
+    pub fn to_pgm_with(&self, maxval: u16, output: &OutputTransform) -> Vec<u8> {
+        assert!(maxval > 0);
+        let mut data = format!("P5\n{} {}\n{}\n", self.width, self.height, maxval).into_bytes();
+        for (i, color) in self.pixels().iter().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            let luminance = color.luminance();
+            let [gray, _, _] =
+                output.quantize(Color::new(luminance, luminance, luminance), x, y, maxval);
+            push_sample(&mut data, gray, maxval);
+        }
+        data
+    }
+
+    // PFM keeps the unclamped float values, rows are stored bottom to top and
+    // the negative scale means little endian
+    pub fn to_pfm(&self) -> Vec<u8> {
+        let mut data = format!("PF\n{} {}\n-1.0\n", self.width, self.height).into_bytes();
+        for row in self.pixels().chunks(self.width).rev() {
+            for color in row {
+                for channel in [color.r, color.g, color.b] {
+                    data.extend_from_slice(&(channel as f32).to_le_bytes());
+                }
+            }
+        }
+        data
+    }
+
+    // read an ascii P3 or binary P6 image, channels are scaled to 0..1
+    pub fn from_ppm(data: &[u8]) -> Result<Canvas, PpmError> {
+        let mut reader = PpmReader { data, position: 0 };
+        let magic = reader.token().ok_or(PpmError::UnexpectedEnd)?;
+        let binary = match magic {
+            "P3" => false,
+            "P6" => true,
+            _ => return Err(PpmError::UnsupportedFormat(magic.to_string())),
+        };
+        let width = reader.number()?;
+        let height = reader.number()?;
+        let maxval = reader.number()?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(PpmError::InvalidHeader(maxval.to_string()));
+        }
+        // a single whitespace separates the header from the binary data
+        reader.position += 1;
+
+        let count = width
+            .checked_mul(height)
+            .ok_or_else(|| PpmError::InvalidHeader(format!("{}x{}", width, height)))?;
+        // don't trust the header for the allocation, the data may be shorter
+        let mut pixels = Vec::with_capacity(count.min(data.len()));
+        for _ in 0..count {
+            let mut channels = [0.; 3];
+            for channel in channels.iter_mut() {
+                let value = if binary {
+                    reader.sample(maxval)?
+                } else {
+                    reader.number()?
+                };
+                if value > maxval {
+                    return Err(PpmError::InvalidData(format!(
+                        "sample {} above maxval {}",
+                        value, maxval
+                    )));
+                }
+                *channel = value as f64 / maxval as f64;
+            }
+            pixels.push(Color::new(channels[0], channels[1], channels[2]));
+        }
+        Ok(Canvas::from_pixels(width, height, pixels))
+    }
+}
+
+struct PpmReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PpmReader<'a> {
+    // the next whitespace separated token, skipping `#` comments
+    fn token(&mut self) -> Option<&'a str> {
+        loop {
+            match self.data.get(self.position)? {
+                b'#' => {
+                    while self.data.get(self.position).is_some_and(|&b| b != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.position += 1,
+                _ => break,
+            }
+        }
+        let start = self.position;
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.position]).ok()
+    }
+
+    fn number(&mut self) -> Result<usize, PpmError> {
+        let token = self.token().ok_or(PpmError::UnexpectedEnd)?;
+        token
+            .parse()
+            .map_err(|_| PpmError::InvalidHeader(token.to_string()))
+    }
+
+    fn sample(&mut self, maxval: usize) -> Result<usize, PpmError> {
+        let size = if maxval < 256 { 1 } else { 2 };
+        let bytes = self
+            .data
+            .get(self.position..self.position + size)
+            .ok_or(PpmError::UnexpectedEnd)?;
+        self.position += size;
+        Ok(bytes.iter().fold(0, |value, &b| value << 8 | b as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PpmError;
+    use crate::canvas::{Canvas, OutputTransform};
+    use crate::color::Color;
+
+    fn canvas() -> Canvas {
+        let mut canvas = Canvas::new(3, 2, Color::black());
+        canvas.write_pixel(0, 0, Color::new(1.5, 0., 0.));
+        canvas.write_pixel(1, 0, Color::new(0., 0.5, 0.));
+        canvas.write_pixel(2, 1, Color::new(-0.5, 0., 1.));
+        canvas
+    }
+
+    #[test]
+    fn it_writes_binary_ppm() {
+        let ppm = canvas().to_ppm_binary(255);
+        let header = b"P6\n3 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 3 * 2 * 3);
+        assert_eq!(
+            &ppm[header.len()..header.len() + 6],
+            &[255, 0, 0, 0, 128, 0]
+        );
+        assert_eq!(&ppm[ppm.len() - 3..], &[0, 0, 255]);
+    }
+
+    #[test]
+    fn it_writes_16_bit_samples_big_endian() {
+        let ppm = Canvas::new(1, 1, Color::new(1., 0.5, 0.)).to_ppm_binary(65535);
+        let header = b"P6\n1 1\n65535\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(&ppm[header.len()..], &[255, 255, 128, 0, 0, 0]);
+    }
+
+    #[test]
+    fn it_writes_the_luminance_as_pgm() {
+        let pgm = Canvas::new(2, 1, Color::white()).to_pgm(255);
+        assert_eq!(pgm, b"P5\n2 1\n255\n\xff\xff");
+    }
+
+    #[test]
+    fn it_writes_unclamped_pfm_from_the_bottom_row() {
+        let pfm = canvas().to_pfm();
+        let header = b"PF\n3 2\n-1.0\n";
+        assert_eq!(&pfm[..header.len()], header);
+        let floats = pfm[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<_>>();
+        assert_eq!(floats.len(), 18);
+        // the bottom row comes first
+        assert_eq!(&floats[6..9], &[-0.5, 0., 1.]);
+        assert_eq!(&floats[9..12], &[1.5, 0., 0.]);
+    }
+
+    #[test]
+    fn it_reads_ascii_ppm_back() {
+        let canvas = canvas();
+        let read = Canvas::from_ppm(canvas.to_ppm().as_bytes()).unwrap();
+        assert_eq!(read.width, 3);
+        assert_eq!(read.height, 2);
+        assert_abs_diff_eq!(*read.read_pixel(0, 0), Color::new(1., 0., 0.));
+        assert_abs_diff_eq!(*read.read_pixel(1, 0), Color::new(0., 128. / 255., 0.));
+        assert_abs_diff_eq!(*read.read_pixel(2, 1), Color::new(0., 0., 1.));
+    }
+
+    #[test]
+    fn it_reads_binary_ppm_back() {
+        for maxval in [255, 65535] {
+            let canvas = canvas();
+            let read = Canvas::from_ppm(&canvas.to_ppm_binary(maxval)).unwrap();
+            assert_abs_diff_eq!(*read.read_pixel(0, 0), Color::new(1., 0., 0.));
+            assert_abs_diff_eq!(*read.read_pixel(2, 1), Color::new(0., 0., 1.));
+        }
+    }
+
+    #[test]
+    fn it_skips_comments_and_scales_by_maxval() {
+        let ppm = b"P3\n# a comment\n2 1 # trailing\n  15\n15 0 0\t\n0 5 15\n";
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+        assert_abs_diff_eq!(*canvas.read_pixel(0, 0), Color::new(1., 0., 0.));
+        assert_abs_diff_eq!(*canvas.read_pixel(1, 0), Color::new(0., 1. / 3., 1.));
+    }
+
+    #[test]
+    fn it_rejects_unsupported_or_truncated_files() {
+        assert_eq!(
+            Canvas::from_ppm(b"P5\n1 1\n255\n\x00"),
+            Err(PpmError::UnsupportedFormat("P5".to_string()))
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P3\n1 x\n255\n"),
+            Err(PpmError::InvalidHeader("x".to_string()))
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P6\n2 1\n255\n\x00\x00\x00"),
+            Err(PpmError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn it_rejects_samples_above_maxval() {
+        assert_eq!(
+            Canvas::from_ppm(b"P3\n1 1\n15\n15 16 0\n"),
+            Err(PpmError::InvalidData(
+                "sample 16 above maxval 15".to_string()
+            ))
+        );
+        assert_eq!(
+            Canvas::from_ppm(b"P6\n1 1\n300\n\x01\x2c\x01\x2d\x00\x00"),
+            Err(PpmError::InvalidData(
+                "sample 301 above maxval 300".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::canvas::ppm::quantize;
+use crate::color::Color;
+
+// compress unbounded radiance into the 0..1 range of the output
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum ToneMap {
+    #[default]
+    Clamp,
+    Reinhard,
+    // channels at `white` and above map to 1, see `extended_reinhard` for a
+    // checked white point
+    ExtendedReinhard {
+        white: f64,
+    },
+    // Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+impl ToneMap {
+    // None unless the white point is positive, the curve divides by it
+    pub fn extended_reinhard(white: f64) -> Option<Self> {
+        (white > 0.).then_some(ToneMap::ExtendedReinhard { white })
+    }
+
+    pub fn apply(&self, channel: f64) -> f64 {
+        let x = channel.max(0.);
+        let mapped = match self {
+            ToneMap::Clamp => x,
+            ToneMap::Reinhard => x / (1. + x),
+            ToneMap::ExtendedReinhard { white } => x * (1. + x / (white * white)) / (1. + x),
+            ToneMap::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+        };
+        mapped.clamp(0., 1.)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Transfer {
+    #[default]
+    Linear,
+    Srgb,
+}
+
+impl Transfer {
+    pub fn encode(&self, channel: f64) -> f64 {
+        match self {
+            Transfer::Linear => channel,
+            Transfer::Srgb if channel <= 0.0031308 => 12.92 * channel,
+            Transfer::Srgb => 1.055 * channel.powf(1. / 2.4) - 0.055,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Dither {
+    #[default]
+    None,
+    // 8x8 Bayer matrix
+    Ordered,
+    // Roberts' R2 sequence, a cheap noise with a blue noise like spectrum
+    BlueNoise,
+}
+
+impl Dither {
+    // the rounding threshold in 0..1 for the pixel, None to round up
+    fn threshold(&self, x: usize, y: usize) -> Option<f64> {
+        match self {
+            Dither::None => None,
+            Dither::Ordered => {
+                let (xy, mut level) = (x ^ y, 0);
+                for bit in 0..3 {
+                    let pair = ((xy >> bit) & 1) << 1 | ((y >> bit) & 1);
+                    level |= pair << (2 * (2 - bit));
+                }
+                Some((level as f64 + 0.5) / 64.)
+            }
+            Dither::BlueNoise => {
+                let value = 0.5 + x as f64 * 0.754_877_666_2 + y as f64 * 0.569_840_290_9;
+                Some(value.fract())
+            }
+        }
+    }
+}
+
+// how a linear canvas becomes displayable integer pixels
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct OutputTransform {
+    // in stops, every stop doubles the brightness
+    pub exposure: f64,
+    pub tone_map: ToneMap,
+    pub transfer: Transfer,
+    pub dither: Dither,
+}
+
+impl OutputTransform {
+    pub fn new(exposure: f64, tone_map: ToneMap, transfer: Transfer, dither: Dither) -> Self {
+        Self {
+            exposure,
+            tone_map,
+            transfer,
+            dither,
+        }
+    }
+
+    // the usual display setup, sRGB encoded with the given tone mapping
+    pub fn srgb(tone_map: ToneMap) -> Self {
+        Self::new(0., tone_map, Transfer::Srgb, Dither::None)
+    }
+
+    // exposed, tone mapped and encoded but not quantized yet
+    pub fn apply(&self, color: Color) -> Color {
+        let scale = self.exposure.exp2();
+        let channel = |c: f64| self.transfer.encode(self.tone_map.apply(c * scale));
+        Color::new(channel(color.r), channel(color.g), channel(color.b))
+    }
+
+    pub fn quantize(&self, color: Color, x: usize, y: usize, maxval: u16) -> [u16; 3] {
+        let color = self.apply(color);
+        [color.r, color.g, color.b].map(|channel| self.quantize_value(channel, x, y, maxval))
+    }
+
+    // only the dithering and the rounding of `quantize`, for a value in 0..1
+    // that is not a color like the alpha
+    pub fn quantize_value(&self, value: f64, x: usize, y: usize, maxval: u16) -> u16 {
+        match self.dither.threshold(x, y) {
+            Some(threshold) => (value * maxval as f64 + threshold)
+                .floor()
+                .clamp(0., maxval as f64) as u16,
+            None => quantize(value, maxval),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dither, OutputTransform, ToneMap, Transfer};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+
+    #[test]
+    fn it_tells_apart_highlights_that_clamping_merges() {
+        for tone_map in [
+            ToneMap::Reinhard,
+            ToneMap::ExtendedReinhard { white: 200. },
+            ToneMap::Aces,
+        ] {
+            assert!(tone_map.apply(1.5) < tone_map.apply(100.));
+            assert!(tone_map.apply(100.) <= 1.);
+            assert_abs_diff_eq!(tone_map.apply(0.), 0.);
+        }
+        assert_abs_diff_eq!(ToneMap::Clamp.apply(1.5), ToneMap::Clamp.apply(100.));
+    }
+
+    #[test]
+    fn it_maps_the_white_point_to_one() {
+        assert_abs_diff_eq!(ToneMap::Reinhard.apply(1.), 0.5);
+        let tone_map = ToneMap::extended_reinhard(4.).unwrap();
+        assert_abs_diff_eq!(tone_map.apply(4.), 1.);
+        assert_abs_diff_eq!(tone_map.apply(8.), 1.);
+    }
+
+    #[test]
+    fn it_rejects_white_points_that_are_not_positive() {
+        for white in [0., -1., f64::NAN] {
+            assert_eq!(ToneMap::extended_reinhard(white), None);
+        }
+    }
+
+    #[test]
+    fn it_encodes_the_srgb_transfer_curve() {
+        assert_abs_diff_eq!(Transfer::Srgb.encode(0.), 0.);
+        assert_abs_diff_eq!(Transfer::Srgb.encode(1.), 1., epsilon = 1e-12);
+        assert_abs_diff_eq!(Transfer::Srgb.encode(0.001), 0.01292);
+        assert_abs_diff_eq!(Transfer::Srgb.encode(0.2140), 0.5, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn it_doubles_the_brightness_every_stop() {
+        let output = OutputTransform::new(2., ToneMap::Clamp, Transfer::Linear, Dither::None);
+        assert_abs_diff_eq!(
+            output.apply(Color::new(0.1, 0.2, 0.)),
+            Color::new(0.4, 0.8, 0.)
+        );
+    }
+
+    #[test]
+    fn it_uses_every_ordered_dither_threshold_once() {
+        let mut thresholds = (0..8)
+            .flat_map(|y| (0..8).map(move |x| Dither::Ordered.threshold(x, y).unwrap()))
+            .map(|t| (t * 64.) as usize)
+            .collect::<Vec<_>>();
+        thresholds.sort();
+        assert_eq!(thresholds, (0..64).collect::<Vec<_>>());
+        assert_eq!(
+            Dither::Ordered.threshold(0, 0),
+            Dither::Ordered.threshold(8, 8)
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_average_level_when_dithering() {
+        for dither in [Dither::Ordered, Dither::BlueNoise] {
+            let output = OutputTransform::new(0., ToneMap::Clamp, Transfer::Linear, dither);
+            // a level between two 8 bit values
+            let level = 100.3 / 255.;
+            let canvas = Canvas::new(64, 64, Color::new(level, level, level));
+            let image = canvas.to_rgb_image_with(&output);
+            let mean = image.pixels().map(|p| p.0[0] as f64).sum::<f64>() / (64. * 64.);
+            assert_abs_diff_eq!(mean, 100.3, epsilon = 0.02);
+            assert!(image.pixels().any(|p| p.0[0] == 100));
+            assert!(image.pixels().any(|p| p.0[0] == 101));
+        }
+    }
+
+    #[test]
+    fn it_applies_the_output_transform_to_netpbm_output() {
+        let output = OutputTransform::srgb(ToneMap::Reinhard);
+        let canvas = Canvas::new(2, 1, Color::new(1., 3., 0.));
+        // 0.5 and 0.75 once tone mapped, then sRGB encoded
+        let expected = [188, 225, 0];
+        assert!(canvas
+            .to_ppm_with(&output)
+            .ends_with("188 225 0 188 225 0\n"));
+        assert_eq!(
+            Color::new(1., 3., 0.).to_ppm_with(&output),
+            expected.map(|channel| channel.to_string())
+        );
+        let ppm = canvas.to_ppm_binary_with(255, &output);
+        assert_eq!(&ppm[ppm.len() - 6..], &[expected, expected].concat()[..]);
+        let pgm = Canvas::new(1, 1, Color::white()).to_pgm_with(255, &output);
+        assert_eq!(pgm.last(), Some(&188));
+    }
+
+    #[test]
+    fn it_rounds_up_like_the_ppm_without_dithering() {
+        let output = OutputTransform::default();
+        assert_eq!(
+            output.quantize(Color::new(1.5, 0.5, -0.5), 3, 4, 255),
+            [255, 128, 0]
+        );
+    }
+}
@@ -1,44 +1,50 @@
-use approx::{AbsDiffEq};
+use crate::canvas::OutputTransform;
+use crate::float::Float;
+use approx::AbsDiffEq;
 use std::ops::{Add, Mul, Sub};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Color {
-    pub r: f64,
-    pub g: f64,
-    pub b: f64,
+pub struct Color<T = f64> {
+    pub r: T,
+    pub g: T,
+    pub b: T,
 }
 
-impl Color {
+impl<T: Float> Color<T> {
     pub fn black() -> Self {
-        Self::new(0., 0., 0.)
+        Self::new(T::ZERO, T::ZERO, T::ZERO)
     }
 
     pub fn white() -> Self {
-        Self::new(1., 1., 1.)
+        Self::new(T::ONE, T::ONE, T::ONE)
     }
 
     pub fn red() -> Self {
-        Self::new(1., 0., 0.)
+        Self::new(T::ONE, T::ZERO, T::ZERO)
     }
 
-    pub fn new(r: f64, g: f64, b: f64) -> Self {
+    pub fn new(r: T, g: T, b: T) -> Self {
         Self { r, g, b }
     }
 
-    fn channel_to_ppm(channel: f64) -> String {
-        format!("{}", (channel * 255.).ceil() as u8)
+    // relative luminance of linear Rec. 709 primaries
+    pub fn luminance(&self) -> T {
+        T::from_f64(0.2126) * self.r + T::from_f64(0.7152) * self.g + T::from_f64(0.0722) * self.b
     }
 
-    pub fn to_ppm(&self) -> [String; 3] {
-        [
-            Color::channel_to_ppm(self.r),
-            Color::channel_to_ppm(self.g),
-            Color::channel_to_ppm(self.b),
-        ]
+    pub fn to_ppm(self) -> [String; 3] {
+        self.to_ppm_with(&OutputTransform::default())
+    }
+
+    pub fn to_ppm_with(self, output: &OutputTransform) -> [String; 3] {
+        let color = Color::new(self.r.to_f64(), self.g.to_f64(), self.b.to_f64());
+        output
+            .quantize(color, 0, 0, 255)
+            .map(|channel| channel.to_string())
     }
 }
 
-impl Add for Color {
+impl<T: Float> Add for Color<T> {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
@@ -50,7 +56,7 @@ impl Add for Color {
     }
 }
 
-impl Sub for Color {
+impl<T: Float> Sub for Color<T> {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
@@ -62,10 +68,10 @@ impl Sub for Color {
     }
 }
 
-impl Mul<f64> for Color {
+impl<T: Float> Mul<T> for Color<T> {
     type Output = Self;
 
-    fn mul(self, other: f64) -> Self {
+    fn mul(self, other: T) -> Self {
         Self {
             r: self.r * other,
             g: self.g * other,
@@ -74,7 +80,7 @@ impl Mul<f64> for Color {
     }
 }
 
-impl Mul<Color> for Color {
+impl<T: Float> Mul<Color<T>> for Color<T> {
     type Output = Self;
 
     fn mul(self, other: Self) -> Self {
@@ -86,7 +92,85 @@ impl Mul<Color> for Color {
     }
 }
 
-impl AbsDiffEq for Color {
+impl<T: Float> AbsDiffEq for Color<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        T::abs_diff_eq(&self.r, &other.r, epsilon)
+            && T::abs_diff_eq(&self.g, &other.g, epsilon)
+            && T::abs_diff_eq(&self.b, &other.b, epsilon)
+    }
+}
+
+// the canvases accumulate the samples in double precision
+impl From<Color<f32>> for Color {
+    fn from(color: Color<f32>) -> Self {
+        Color::new(color.r as f64, color.g as f64, color.b as f64)
+    }
+}
+
+// a color with an alpha coverage, the color is premultiplied by the alpha so
+// that filtering and compositing are plain sums
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rgba {
+    pub color: Color,
+    pub alpha: f64,
+}
+
+impl Rgba {
+    // `color` is not premultiplied yet
+    pub fn new(color: Color, alpha: f64) -> Self {
+        Self {
+            color: color * alpha,
+            alpha,
+        }
+    }
+
+    pub fn opaque(color: Color) -> Self {
+        Self::new(color, 1.)
+    }
+
+    pub fn transparent() -> Self {
+        Self::new(Color::black(), 0.)
+    }
+
+    // the color divided back by the alpha, black when fully transparent
+    pub fn straight(&self) -> Color {
+        if self.alpha == 0. {
+            Color::black()
+        } else {
+            self.color * (1. / self.alpha)
+        }
+    }
+}
+
+impl Add for Rgba {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            color: self.color + other.color,
+            alpha: self.alpha + other.alpha,
+        }
+    }
+}
+
+impl Mul<f64> for Rgba {
+    type Output = Self;
+
+    fn mul(self, other: f64) -> Self {
+        Self {
+            color: self.color * other,
+            alpha: self.alpha * other,
+        }
+    }
+}
+
+impl AbsDiffEq for Rgba {
     type Epsilon = f64;
 
     fn default_epsilon() -> Self::Epsilon {
@@ -94,15 +178,14 @@ impl AbsDiffEq for Color {
     }
 
     fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
-        f64::abs_diff_eq(&self.r, &other.r, epsilon)
-            && f64::abs_diff_eq(&self.g, &other.g, epsilon)
-            && f64::abs_diff_eq(&self.b, &other.b, epsilon)
+        Color::abs_diff_eq(&self.color, &other.color, epsilon)
+            && f64::abs_diff_eq(&self.alpha, &other.alpha, epsilon)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Color;
+    use super::{Color, Rgba};
 
     #[test]
     fn it_creates_colors() {
@@ -119,6 +202,13 @@ mod tests {
         assert_abs_diff_eq!(color1 + color2, Color::new(1.6, 0.7, 1.0));
     }
 
+    #[test]
+    fn it_mixes_single_precision_colors() {
+        let color = Color::<f32>::new(0.9, 0.6, 0.75) * Color::new(0.5, 2., 0.);
+        assert_abs_diff_eq!(color, Color::new(0.45, 1.2, 0.));
+        assert_abs_diff_eq!(Color::<f32>::white().luminance(), 1.);
+    }
+
     #[test]
     fn it_substracts_one_color_from_another() {
         let color1 = Color::new(0.9, 0.6, 0.75);
@@ -132,10 +222,30 @@ mod tests {
         assert_abs_diff_eq!(color * 2.0, Color::new(0.4, 0.6, 0.8));
     }
 
+    #[test]
+    fn it_computes_the_luminance() {
+        assert_abs_diff_eq!(Color::<f64>::white().luminance(), 1., epsilon = 1e-12);
+        assert_abs_diff_eq!(Color::new(0., 1., 0.).luminance(), 0.7152);
+    }
+
     #[test]
     fn it_multiplies_two_colors() {
         let color1 = Color::new(1., 0.2, 0.4);
         let color2 = Color::new(0.9, 1., 0.1);
         assert_abs_diff_eq!(color1 * color2, Color::new(0.9, 0.2, 0.04));
     }
+
+    #[test]
+    fn it_premultiplies_the_alpha() {
+        let rgba = Rgba::new(Color::new(1., 0.5, 0.), 0.5);
+        assert_abs_diff_eq!(rgba.color, Color::new(0.5, 0.25, 0.));
+        assert_abs_diff_eq!(rgba.straight(), Color::new(1., 0.5, 0.));
+        assert_abs_diff_eq!(Rgba::transparent().straight(), Color::black());
+    }
+
+    #[test]
+    fn it_averages_premultiplied_colors() {
+        let half = Rgba::opaque(Color::red()) * 0.5 + Rgba::transparent() * 0.5;
+        assert_abs_diff_eq!(half, Rgba::new(Color::red(), 0.5));
+    }
 }
@@ -0,0 +1,191 @@
+use crate::simd;
+use approx::AbsDiffEq;
+use std::fmt::{Debug, Display};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// the scalar the tuples, matrices and colors are generic over, f32 halves
+// their size while f64 keeps the precision. Implemented for those two only,
+// the tuples need the kernels of simd::Kernels.
+pub trait Float:
+    simd::Kernels
+    + Copy
+    + Default
+    + Debug
+    + Display
+    + PartialOrd
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + AbsDiffEq<Epsilon = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const EPSILON: Self;
+    const INFINITY: Self;
+    // default tolerance when comparing results of long chains of products,
+    // like matrix multiplications and inversions
+    const APPROX_EPSILON: Self;
+
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn powf(self, exponent: Self) -> Self;
+    fn powi(self, exponent: i32) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn clamp(self, min: Self, max: Self) -> Self;
+    fn total_cmp(&self, other: &Self) -> std::cmp::Ordering;
+}
+
+// inlined, the generic code calling them is also compiled in other crates
+macro_rules! impl_float {
+    ($type:ident, $approx_epsilon:expr) => {
+        impl Float for $type {
+            const ZERO: Self = 0.;
+            const ONE: Self = 1.;
+            const EPSILON: Self = $type::EPSILON;
+            const INFINITY: Self = $type::INFINITY;
+            const APPROX_EPSILON: Self = $approx_epsilon;
+
+            #[inline]
+            fn from_f64(value: f64) -> Self {
+                value as $type
+            }
+
+            #[inline]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            #[inline]
+            fn abs(self) -> Self {
+                $type::abs(self)
+            }
+
+            #[inline]
+            fn sqrt(self) -> Self {
+                $type::sqrt(self)
+            }
+
+            #[inline]
+            fn powf(self, exponent: Self) -> Self {
+                $type::powf(self, exponent)
+            }
+
+            #[inline]
+            fn powi(self, exponent: i32) -> Self {
+                $type::powi(self, exponent)
+            }
+
+            #[inline]
+            fn sin(self) -> Self {
+                $type::sin(self)
+            }
+
+            #[inline]
+            fn cos(self) -> Self {
+                $type::cos(self)
+            }
+
+            #[inline]
+            fn tan(self) -> Self {
+                $type::tan(self)
+            }
+
+            #[inline]
+            fn asin(self) -> Self {
+                $type::asin(self)
+            }
+
+            #[inline]
+            fn acos(self) -> Self {
+                $type::acos(self)
+            }
+
+            #[inline]
+            fn atan2(self, other: Self) -> Self {
+                $type::atan2(self, other)
+            }
+
+            #[inline]
+            fn floor(self) -> Self {
+                $type::floor(self)
+            }
+
+            #[inline]
+            fn ceil(self) -> Self {
+                $type::ceil(self)
+            }
+
+            #[inline]
+            fn round(self) -> Self {
+                $type::round(self)
+            }
+
+            #[inline]
+            fn min(self, other: Self) -> Self {
+                $type::min(self, other)
+            }
+
+            #[inline]
+            fn max(self, other: Self) -> Self {
+                $type::max(self, other)
+            }
+
+            #[inline]
+            fn clamp(self, min: Self, max: Self) -> Self {
+                $type::clamp(self, min, max)
+            }
+
+            #[inline]
+            fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+                $type::total_cmp(self, other)
+            }
+        }
+    };
+}
+
+impl_float!(f32, 1e-5);
+impl_float!(f64, 1e-14);
+
+#[cfg(test)]
+mod tests {
+    use super::Float;
+
+    fn hypotenuse<T: Float>(a: T, b: T) -> T {
+        (a * a + b * b).sqrt()
+    }
+
+    #[test]
+    fn it_computes_the_same_in_both_precisions() {
+        assert_abs_diff_eq!(hypotenuse(3_f32, 4.), 5.);
+        assert_abs_diff_eq!(hypotenuse(3_f64, 4.), 5.);
+        assert_abs_diff_eq!(f32::from_f64(0.1).to_f64(), 0.1, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn it_loosens_the_comparisons_for_single_precision() {
+        assert!(f32::APPROX_EPSILON > f64::APPROX_EPSILON as f32);
+        assert_eq!(<f32 as Float>::EPSILON, f32::EPSILON);
+    }
+}
@@ -0,0 +1,357 @@
+use crate::float::Float;
+use crate::geo::{Ray, RayPacket};
+use crate::matrix::{AnimatedTransform, Decomposition, Matrix4, Quaternion};
+use crate::tuple::{Point3, Vector3};
+use approx::AbsDiffEq;
+
+// axis aligned box, empty when `min` is above `max` on any axis
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox<T = f64> {
+    pub min: Point3<T>,
+    pub max: Point3<T>,
+}
+
+impl<T: Float> BoundingBox<T> {
+    pub fn new(min: Point3<T>, max: Point3<T>) -> Self {
+        Self { min, max }
+    }
+
+    // contains nothing, including a point or a box into it gives that one
+    pub fn empty() -> Self {
+        Self {
+            min: Point3::point(T::INFINITY, T::INFINITY, T::INFINITY),
+            max: Point3::point(-T::INFINITY, -T::INFINITY, -T::INFINITY),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn contains(&self, point: Point3<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn including(&self, point: Point3<T>) -> Self {
+        Self {
+            min: Point3::point(
+                self.min.x.min(point.x),
+                self.min.y.min(point.y),
+                self.min.z.min(point.z),
+            ),
+            max: Point3::point(
+                self.max.x.max(point.x),
+                self.max.y.max(point.y),
+                self.max.z.max(point.z),
+            ),
+        }
+    }
+
+    pub fn union(&self, other: &BoundingBox<T>) -> Self {
+        if other.is_empty() {
+            return *self;
+        }
+        self.including(other.min).including(other.max)
+    }
+
+    // the box around the 8 transformed corners, it grows when rotated
+    pub fn transform(&self, matrix: &Matrix4<T>) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        self.corners().iter().fold(Self::empty(), |bounds, corner| {
+            bounds.including(matrix * *corner)
+        })
+    }
+
+    // contains the box at every time of the motion, not only at its ends.
+    // Without a change of rotation each coordinate of a corner is a quadratic
+    // in time, from the shear times the scale, and it's bounded exactly by
+    // its ends and its vertex. A rotation that changes is bounded by the
+    // sphere the sheared and scaled box sweeps around the translation.
+    pub fn transform_animated(&self, motion: &AnimatedTransform<T>) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        let (start, end) = (motion.start().decompose(), motion.end().decompose());
+        let half = T::from_f64(0.5);
+        // the corner at the start, half way and at the end of the motion
+        let samples = |corner: Point3<T>, placed: &dyn Fn(Decomposition<T>) -> Decomposition<T>| {
+            [T::ZERO, half, T::ONE]
+                .map(|t| placed(start.interpolate(&end, t)).to_matrix() * corner)
+                .map(|point| [point.x, point.y, point.z])
+        };
+        let corners = self.corners();
+        if start.rotation == end.rotation {
+            return corners.iter().fold(Self::empty(), |bounds, corner| {
+                let [p0, ph, p1] = samples(*corner, &|d| d);
+                let [x, y, z] = [0, 1, 2].map(|axis| quadratic_range(p0[axis], ph[axis], p1[axis]));
+                bounds
+                    .including(Point3::point(x.0, y.0, z.0))
+                    .including(Point3::point(x.1, y.1, z.1))
+            });
+        }
+        let radius = corners.iter().fold(T::ZERO, |radius, corner| {
+            let unplaced = |d| Decomposition {
+                translation: Vector3::vector(T::ZERO, T::ZERO, T::ZERO),
+                rotation: Quaternion::identity(),
+                ..d
+            };
+            let [p0, ph, p1] = samples(*corner, &unplaced);
+            let farthest = [0, 1, 2].map(|axis| {
+                let (min, max) = quadratic_range(p0[axis], ph[axis], p1[axis]);
+                min.abs().max(max.abs())
+            });
+            radius.max(Vector3::vector(farthest[0], farthest[1], farthest[2]).magnitude())
+        });
+        let (t0, t1) = (start.translation, end.translation);
+        Self::new(
+            Point3::point(t0.x.min(t1.x), t0.y.min(t1.y), t0.z.min(t1.z)),
+            Point3::point(t0.x.max(t1.x), t0.y.max(t1.y), t0.z.max(t1.z)),
+        )
+        .grown(radius)
+    }
+
+    fn corners(&self) -> [Point3<T>; 8] {
+        std::array::from_fn(|corner| {
+            let pick = |bit: usize, min: T, max: T| if corner & bit == 0 { min } else { max };
+            Point3::point(
+                pick(1, self.min.x, self.max.x),
+                pick(2, self.min.y, self.max.y),
+                pick(4, self.min.z, self.max.z),
+            )
+        })
+    }
+
+    fn grown(&self, margin: T) -> Self {
+        let margin = Vector3::vector(margin, margin, margin);
+        Self::new(self.min - margin, self.max + margin)
+    }
+
+    // slab test, only counts the box in front of the origin of the ray
+    pub fn intersects(&self, ray: &Ray<T>) -> bool {
+        let (near, far) = slabs(
+            [self.min.x, self.min.y, self.min.z],
+            [self.max.x, self.max.y, self.max.z],
+            [ray.origin.x, ray.origin.y, ray.origin.z],
+            [ray.direction.x, ray.direction.y, ray.direction.z],
+        );
+        far >= near.max(T::ZERO)
+    }
+
+    // the slab test for every lane of the packet at once
+    pub fn intersects_packet<const N: usize>(&self, packet: &RayPacket<N, T>) -> [bool; N] {
+        let min = [self.min.x, self.min.y, self.min.z];
+        let max = [self.max.x, self.max.y, self.max.z];
+        let mut near = [T::ZERO; N];
+        let mut far = [T::INFINITY; N];
+        for axis in 0..3 {
+            let (origins, directions) = (&packet.origins[axis], &packet.directions[axis]);
+            for lane in 0..N {
+                let inverse = T::ONE / directions[lane];
+                let t1 = (min[axis] - origins[lane]) * inverse;
+                let t2 = (max[axis] - origins[lane]) * inverse;
+                near[lane] = near[lane].max(t1.min(t2));
+                far[lane] = far[lane].min(t1.max(t2));
+            }
+        }
+        std::array::from_fn(|lane| far[lane] >= near[lane])
+    }
+}
+
+impl<T: Float> Default for BoundingBox<T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+// the range over [0, 1] of the quadratic taking these values at 0, 0.5 and 1
+fn quadratic_range<T: Float>(start: T, half: T, end: T) -> (T, T) {
+    let two = T::from_f64(2.);
+    let (a, b) = (two * (start + end) - two * two * half, end - start);
+    let mut range = (start.min(end), start.max(end));
+    // the vertex of start + (b - a) t + a t^2
+    let vertex = (a - b) / (two * a);
+    if a != T::ZERO && vertex > T::ZERO && vertex < T::ONE {
+        let value = start + (b - a) * vertex + a * vertex * vertex;
+        range = (range.0.min(value), range.1.max(value));
+    }
+    range
+}
+
+// the distances to the entry and exit of the box, the ray misses it when the
+// entry comes after the exit. A zero direction gives infinite distances
+// that min and max skip past when they turn into NaNs.
+fn slabs<T: Float>(min: [T; 3], max: [T; 3], origin: [T; 3], direction: [T; 3]) -> (T, T) {
+    (0..3).fold((-T::INFINITY, T::INFINITY), |(near, far), axis| {
+        let inverse = T::ONE / direction[axis];
+        let t1 = (min[axis] - origin[axis]) * inverse;
+        let t2 = (max[axis] - origin[axis]) * inverse;
+        (near.max(t1.min(t2)), far.min(t1.max(t2)))
+    })
+}
+
+impl<T: Float> AbsDiffEq for BoundingBox<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        Point3::abs_diff_eq(&self.min, &other.min, epsilon)
+            && Point3::abs_diff_eq(&self.max, &other.max, epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundingBox;
+    use crate::geo::{Ray, RayPacket};
+    use crate::matrix::{AnimatedTransform, Decomposition, Matrix4, Quaternion};
+    use crate::tuple::{Point3, Vector3};
+    use std::f64::consts::PI;
+
+    fn unit() -> BoundingBox {
+        BoundingBox::new(Point3::point(-1., -1., -1.), Point3::point(1., 1., 1.))
+    }
+
+    #[test]
+    fn it_grows_to_include_points_and_boxes() {
+        let bounds = BoundingBox::empty();
+        assert!(bounds.is_empty());
+        let bounds = bounds
+            .including(Point3::point(-5., 2., 0.))
+            .including(Point3::point(7., 0., -3.));
+        assert_abs_diff_eq!(
+            bounds,
+            BoundingBox::new(Point3::point(-5., 0., -3.), Point3::point(7., 2., 0.))
+        );
+        assert!(bounds.contains(Point3::point(0., 1., -1.)));
+        assert!(!bounds.contains(Point3::point(0., 3., -1.)));
+        let union = bounds.union(&unit()).union(&BoundingBox::empty());
+        assert_abs_diff_eq!(
+            union,
+            BoundingBox::new(Point3::point(-5., -1., -3.), Point3::point(7., 2., 1.))
+        );
+    }
+
+    #[test]
+    fn it_is_transformed_through_its_corners() {
+        let bounds = unit().transform(&Matrix4::scale_matrix(2., 1., 1.).translate(1., 0., 0.));
+        assert_abs_diff_eq!(
+            bounds,
+            BoundingBox::new(Point3::point(-1., -1., -1.), Point3::point(3., 1., 1.))
+        );
+        let rotated = unit().transform(&Matrix4::rotation_y_matrix(PI / 4.));
+        let half_diagonal = 2_f64.sqrt();
+        assert_abs_diff_eq!(
+            rotated,
+            BoundingBox::new(
+                Point3::point(-half_diagonal, -1., -half_diagonal),
+                Point3::point(half_diagonal, 1., half_diagonal)
+            ),
+            epsilon = 1e-12
+        );
+        assert!(BoundingBox::empty()
+            .transform(&Matrix4::translation_matrix(1., 2., 3.))
+            .is_empty());
+    }
+
+    // every box the motion goes through lies inside `bounds`
+    fn covers_the_motion(bounds: &BoundingBox, motion: &AnimatedTransform) -> bool {
+        (0..=100).all(|i| {
+            let moved = unit().transform(&motion.at(i as f64 / 100.));
+            bounds.contains(moved.min) && bounds.contains(moved.max)
+        })
+    }
+
+    #[test]
+    fn it_bounds_the_whole_of_a_motion() {
+        let moved = Matrix4::rotation_y_matrix(PI / 2.).translate(4., 0., 0.);
+        let motion = AnimatedTransform::new(Matrix4::identity(), moved, 0., 1.);
+        let bounds = unit().transform_animated(&motion);
+        assert!(covers_the_motion(&bounds, &motion));
+        // the ends alone miss the corners turned by 45 degrees half way
+        let ends = unit()
+            .transform(&motion.start())
+            .union(&unit().transform(&moved));
+        assert!(!covers_the_motion(&ends, &motion));
+
+        let sheared = Matrix4::shear_matrix(0.5, -1., 0., 2., 0., 0.)
+            * Matrix4::scale_matrix(3., -1., 0.5).translate(0., 2., 0.);
+        let motion = AnimatedTransform::new(Matrix4::scale_matrix(1., 2., 1.), sheared, 0., 1.);
+        assert!(covers_the_motion(
+            &unit().transform_animated(&motion),
+            &motion
+        ));
+
+        // the ends are enough without rotation
+        let motion = AnimatedTransform::new(
+            Matrix4::identity(),
+            Matrix4::scale_matrix(2., 1., 1.).translate(3., 0., 0.),
+            0.,
+            1.,
+        );
+        assert_abs_diff_eq!(
+            unit().transform_animated(&motion),
+            BoundingBox::new(Point3::point(-1., -1., -1.), Point3::point(5., 1., 1.)),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_intersects_rays() {
+        let bounds = unit();
+        let cases = [
+            (
+                Point3::point(5., 0.5, 0.),
+                Vector3::vector(-1., 0., 0.),
+                true,
+            ),
+            (
+                Point3::point(0., 0., -5.),
+                Vector3::vector(0., 0., 1.),
+                true,
+            ),
+            (
+                Point3::point(0.5, 0., 0.),
+                Vector3::vector(0., 0., 1.),
+                true,
+            ),
+            (
+                Point3::point(-2., 0., 0.),
+                Vector3::vector(2., 4., 6.),
+                false,
+            ),
+            (
+                Point3::point(2., 2., 0.),
+                Vector3::vector(0., 0., 1.),
+                false,
+            ),
+            // the box is behind the ray
+            (
+                Point3::point(0., 0., 5.),
+                Vector3::vector(0., 0., 1.),
+                false,
+            ),
+        ];
+        for (origin, direction, expected) in cases {
+            assert_eq!(bounds.intersects(&Ray::new(origin, direction)), expected);
+        }
+    }
+
+    #[test]
+    fn it_intersects_packets_like_single_rays() {
+        let bounds = unit().transform(&Matrix4::translation_matrix(0., 0., 3.));
+        let rays = [-3., -1.5, -0.5, 0., 0.5, 1., 1.5, 3.]
+            .map(|x| Ray::new(Point3::point(0., 0., -5.), Vector3::vector(x / 8., 0., 1.)));
+        let hits = bounds.intersects_packet(&RayPacket::new(rays));
+        for (ray, hit) in rays.iter().zip(hits) {
+            assert_eq!(hit, bounds.intersects(ray));
+        }
+        assert_eq!(hits.iter().filter(|hit| **hit).count(), 4);
+    }
+}
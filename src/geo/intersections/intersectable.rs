@@ -1,42 +1,106 @@
 // use core::fmt::Debug;
 use std::fmt;
 use std::any::Any;
+use std::sync::Arc;
 use core::fmt::Debug;
-use crate::{geo::{Ray, Intersections, Sphere}, tuple::{Point3, Vector3}, scene::Material};
+use crate::{geo::{Ray, Intersections, Sphere}, tuple::{Normal3, Point3}, scene::Material};
+use crate::float::Float;
+use crate::geo::{BoundingBox, Intersection, RayPacket, Shape};
 
-#[derive(Debug, PartialEq)]
-pub enum Intersectable  { // <>
-    Sphere(Sphere),
+// the built-in shapes are kept by value, boxing them would allocate for
+// every intersection
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum Intersectable<T: Float = f64>  { // <>
+    Sphere(Sphere<T>),
+    // shapes implemented outside of the crate, shared between their
+    // intersections instead of copied like the built-in ones
+    Shape(Arc<dyn Shape<T>>),
 }
 
-impl Intersectable {
-    pub fn intersections(&self, ray: &Ray) -> Intersections {
+impl<T: Float> Intersectable<T> {
+    pub fn shape<S: Shape<T> + 'static>(shape: S) -> Self {
+        Intersectable::Shape(Arc::new(shape))
+    }
+
+    pub fn intersections(&self, ray: &Ray<T>) -> Intersections<T> {
         match self {
             Intersectable::Sphere(s) => {
                 s.intersections(ray)
             }
+            Intersectable::Shape(s) => {
+                let local_ray = ray.transform(&s.transform().inverse());
+                Intersections::new(
+                    s.local_intersect(&local_ray)
+                        .into_iter()
+                        .map(|t| Intersection::new(t, self.clone()))
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    pub fn intersections_packet<const N: usize>(
+        &self,
+        packet: &RayPacket<N, T>,
+    ) -> [Intersections<T>; N] {
+        match self {
+            Intersectable::Sphere(s) => {
+                s.intersections_packet(packet)
+            }
+            Intersectable::Shape(_) => {
+                packet.rays().map(|ray| self.intersections(&ray))
+            }
+        }
+    }
+
+    pub fn world_bounds(&self) -> BoundingBox<T> {
+        match self {
+            Intersectable::Sphere(s) => {
+                s.world_bounds()
+            }
+            Intersectable::Shape(s) => {
+                s.world_bounds()
+            }
         }
     }
 
-    pub fn normal_at(&self, position: Point3) -> Vector3 {
+    pub fn normal_at(&self, position: Point3<T>) -> Normal3<T> {
         match self {
             Intersectable::Sphere(s) => {
                 s.normal_at(position)
             }
+            Intersectable::Shape(s) => {
+                s.normal_at(position)
+            }
         }
     }
 
-    pub fn get_material(&self) -> Material {
+    pub fn get_material(&self) -> Material<T> {
         match self {
             Intersectable::Sphere(s) => {
                 s.material
             }
+            Intersectable::Shape(s) => {
+                s.material()
+            }
+        }
+    }
+}
+
+// the same shape, there's no way to compare the ones from other crates
+impl<T: Float> PartialEq for Intersectable<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Intersectable::Sphere(a), Intersectable::Sphere(b)) => a == b,
+            (Intersectable::Shape(a), Intersectable::Shape(b)) => Arc::ptr_eq(a, b),
+            _ => false,
         }
     }
 }
 
-impl fmt::Display for Intersectable {
+impl<T: Float> fmt::Display for Intersectable<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self)
+        write!(f, "{:?}", self)
     }
 }
@@ -1,23 +1,24 @@
 // use core::fmt::Debug;
+use crate::float::Float;
 use crate::geo::{Intersectable, Ray, Sphere};
 use std::any::Any;
 use std::fmt;
 #[derive(Debug, PartialEq)]
-pub struct Intersection {
-    pub t: f64,
-    pub intersectable: Intersectable,
+pub struct Intersection<T: Float = f64> {
+    pub t: T,
+    pub intersectable: Intersectable<T>,
 }
 
-impl Intersection {
-    pub fn new(t: f64, intersectable: Intersectable) -> Self {
+impl<T: Float> Intersection<T> {
+    pub fn new(t: T, intersectable: Intersectable<T>) -> Self {
         Self {
-            t: t,
+            t,
             intersectable,
         }
     }
 }
 
-impl fmt::Display for Intersection {
+impl<T: Float> fmt::Display for Intersection<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({}:{})", self.t, self.intersectable)
     }
@@ -32,7 +33,7 @@ mod tests {
     fn it_encapsulates_a_parameter_t_and_an_intersectable() {
         let s = Sphere::unit();
         let i = Intersection::new(3.5, Intersectable::Sphere(s));
-        let Intersectable::Sphere(s1) = i.intersectable;
+        let Intersectable::Sphere(s1) = i.intersectable else { unreachable!() };
         assert_eq!(s, s1);
     }
 }
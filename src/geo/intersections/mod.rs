@@ -1,14 +1,15 @@
 use std::ops::Index;
+use crate::float::Float;
 use crate::geo::{Intersection, Intersectable};
 pub mod intersection;
 pub mod intersectable;
 
-pub struct Intersections {
-    data: Vec<Intersection>,
+pub struct Intersections<T: Float = f64> {
+    data: Vec<Intersection<T>>,
 }
 
-impl Intersections {
-    pub fn new(intersections: Vec<Intersection>) -> Self {
+impl<T: Float> Intersections<T> {
+    pub fn new(intersections: Vec<Intersection<T>>) -> Self {
         let mut data = intersections;
         data.sort_unstable_by(|a,b| a.t.partial_cmp(&b.t).unwrap());
         Self { data  }
@@ -18,13 +19,13 @@ impl Intersections {
         self.data.len()
     }
 
-    pub fn hit(&self) -> Option<&Intersection> {
-        self.data.iter().find(|i| i.t >= 0.0)
+    pub fn hit(&self) -> Option<&Intersection<T>> {
+        self.data.iter().find(|i| i.t >= T::ZERO)
     }
 }
 
-impl Index<usize> for Intersections {
-    type Output = Intersection;
+impl<T: Float> Index<usize> for Intersections<T> {
+    type Output = Intersection<T>;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.data[index]
@@ -1,9 +1,15 @@
 mod ray;
 mod sphere;
 mod intersections;
+mod bounding_box;
+mod packet;
+mod shape;
 
 pub use ray::Ray;
+pub use bounding_box::BoundingBox;
+pub use packet::{RayPacket, RayPacket4, RayPacket8};
 pub use sphere::Sphere;
+pub use shape::Shape;
 pub use intersections::Intersections;
 pub use intersections::intersection::Intersection;
 pub use intersections::intersectable::Intersectable;
@@ -0,0 +1,107 @@
+use crate::float::Float;
+use crate::geo::Ray;
+use crate::matrix::Matrix4;
+use crate::tuple::{Point3, Vector3};
+
+// N coherent rays stored by component, x, y and z each hold one value per
+// lane, so that the loops over the lanes vectorize
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RayPacket<const N: usize, T = f64> {
+    pub origins: [[T; N]; 3],
+    pub directions: [[T; N]; 3],
+    pub times: [T; N],
+}
+
+pub type RayPacket4<T = f64> = RayPacket<4, T>;
+pub type RayPacket8<T = f64> = RayPacket<8, T>;
+
+impl<const N: usize, T: Float> RayPacket<N, T> {
+    pub fn new(rays: [Ray<T>; N]) -> Self {
+        Self {
+            origins: [
+                rays.map(|ray| ray.origin.x),
+                rays.map(|ray| ray.origin.y),
+                rays.map(|ray| ray.origin.z),
+            ],
+            directions: [
+                rays.map(|ray| ray.direction.x),
+                rays.map(|ray| ray.direction.y),
+                rays.map(|ray| ray.direction.z),
+            ],
+            times: rays.map(|ray| ray.time),
+        }
+    }
+
+    pub fn ray(&self, lane: usize) -> Ray<T> {
+        let [x, y, z] = self.origins.map(|values| values[lane]);
+        let [dx, dy, dz] = self.directions.map(|values| values[lane]);
+        Ray::with_time(
+            Point3::point(x, y, z),
+            Vector3::vector(dx, dy, dz),
+            self.times[lane],
+        )
+    }
+
+    pub fn rays(&self) -> [Ray<T>; N] {
+        std::array::from_fn(|lane| self.ray(lane))
+    }
+
+    // Ray::transform on every lane, the points with a w of 1 and the
+    // directions with a w of 0
+    pub fn transform(&self, matrix: &Matrix4<T>) -> Self {
+        let apply = |values: &[[T; N]; 3], w: T| -> [[T; N]; 3] {
+            let mut res = [[T::ZERO; N]; 3];
+            for (row, res) in res.iter_mut().enumerate() {
+                let [m0, m1, m2, m3] = [0, 1, 2, 3].map(|col| matrix[(row, col)]);
+                for lane in 0..N {
+                    res[lane] =
+                        m0 * values[0][lane] + m1 * values[1][lane] + m2 * values[2][lane] + m3 * w;
+                }
+            }
+            res
+        };
+        Self {
+            origins: apply(&self.origins, T::ONE),
+            directions: apply(&self.directions, T::ZERO),
+            times: self.times,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RayPacket, RayPacket4};
+    use crate::geo::Ray;
+    use crate::matrix::Matrix4;
+    use crate::tuple::{Point3, Vector3};
+
+    fn rays() -> [Ray; 4] {
+        [0., 1., 2., 3.].map(|i| {
+            Ray::with_time(
+                Point3::point(i, 2. * i, -5.),
+                Vector3::vector(0.1 * i, -0.2, 1.),
+                i / 4.,
+            )
+        })
+    }
+
+    #[test]
+    fn it_keeps_the_rays_per_lane() {
+        let packet = RayPacket4::new(rays());
+        assert_eq!(packet.origins[1], [0., 2., 4., 6.]);
+        assert_eq!(packet.times, [0., 0.25, 0.5, 0.75]);
+        assert_eq!(packet.rays(), rays());
+        assert_eq!(packet.ray(2), rays()[2]);
+    }
+
+    #[test]
+    fn it_transforms_every_lane_like_a_single_ray() {
+        let matrix = Matrix4::rotation_x_matrix(0.4)
+            .scale(2., 3., 1.)
+            .translate(1., -1., 4.);
+        let packet = RayPacket::new(rays()).transform(&matrix);
+        for (lane, ray) in rays().iter().enumerate() {
+            assert_abs_diff_eq!(packet.ray(lane), ray.transform(&matrix), epsilon = 1e-12);
+        }
+    }
+}
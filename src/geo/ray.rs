@@ -1,45 +1,59 @@
+use crate::float::Float;
 use crate::geo::{Intersectable, Intersection, Intersections, Sphere};
 use crate::matrix::Matrix;
 use crate::tuple::{Point3, Vector3};
 use approx::AbsDiffEq;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Ray {
-    pub origin: Point3,
-    pub direction: Vector3,
+pub struct Ray<T = f64> {
+    pub origin: Point3<T>,
+    pub direction: Vector3<T>,
+    pub time: T,
 }
 
-impl Ray {
-    pub fn new(origin: Point3, direction: Vector3) -> Self {
-        Self { origin, direction }
+impl<T: Float> Ray<T> {
+    pub fn new(origin: Point3<T>, direction: Vector3<T>) -> Self {
+        Self::with_time(origin, direction, T::ZERO)
     }
 
-    pub fn position(&self, distance: f64) -> Point3 {
-        self.origin + self.direction * distance
+    pub fn with_time(origin: Point3<T>, direction: Vector3<T>, time: T) -> Self {
+        Self {
+            origin,
+            direction,
+            time,
+        }
     }
 
-    pub fn intersect(&self, intersectable: &Intersectable) -> Intersections {
-        intersectable.intersections(&self)
+    pub fn position(&self, distance: T) -> Point3<T> {
+        self.origin + self.direction * distance
     }
 
-    pub fn transform(&self, matrix: &Matrix<4, 4>) -> Self {
+    pub fn transform(&self, matrix: &Matrix<4, 4, T>) -> Self {
         Self {
-            origin:  matrix * self.origin,
-            direction: matrix * self.direction
+            origin: matrix * self.origin,
+            direction: matrix * self.direction,
+            time: self.time,
         }
     }
 }
 
-impl AbsDiffEq for Ray {
-    type Epsilon = f64;
+impl<T: Float> Ray<T> {
+    pub fn intersect(&self, intersectable: &Intersectable<T>) -> Intersections<T> {
+        intersectable.intersections(self)
+    }
+}
+
+impl<T: Float> AbsDiffEq for Ray<T> {
+    type Epsilon = T;
 
     fn default_epsilon() -> Self::Epsilon {
-        f64::EPSILON
+        T::EPSILON
     }
 
-    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
         Point3::abs_diff_eq(&self.origin, &other.origin, epsilon)
             && Vector3::abs_diff_eq(&self.direction, &other.direction, epsilon)
+            && T::abs_diff_eq(&self.time, &other.time, epsilon)
     }
 }
 
@@ -130,6 +144,14 @@ mod tests {
         assert_abs_diff_eq!(r2.direction, Vector3::vector(0., 1., 0.));
     }
 
+    #[test]
+    fn it_keeps_its_time_when_transformed() {
+        let r1 = Ray::with_time(Point3::point(1., 2., 3.), Vector3::vector(0., 1., 0.), 0.25);
+        let r2 = r1.transform(&Matrix::translation_matrix(3., 4., 5.));
+        assert_abs_diff_eq!(r2.time, 0.25);
+        assert_abs_diff_eq!(Ray::new(r1.origin, r1.direction).time, 0.);
+    }
+
     #[test]
     fn it_can_be_scaled() {
         let r1 = Ray::new(Point3::point(1., 2., 3.), Vector3::vector(0., 1., 0.));
@@ -138,4 +160,12 @@ mod tests {
         assert_abs_diff_eq!(r2.origin, Point3::point(2., 6., 12.));
         assert_abs_diff_eq!(r2.direction, Vector3::vector(0., 3., 0.));
     }
+
+    #[test]
+    fn it_transforms_single_precision_rays() {
+        let r1 = Ray::<f32>::new(Point3::point(1., 2., 3.), Vector3::vector(0., 1., 0.));
+        let r2 = r1.transform(&Matrix::translation_matrix(3., 4., 5.).scale(2., 2., 2.));
+        assert_abs_diff_eq!(r2.origin, Point3::point(8., 12., 16.));
+        assert_abs_diff_eq!(r2.position(0.5), Point3::point(8., 13., 16.));
+    }
 }
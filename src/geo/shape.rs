@@ -0,0 +1,150 @@
+use crate::float::Float;
+use crate::geo::{BoundingBox, Ray};
+use crate::matrix::Transform;
+use crate::scene::Material;
+use crate::tuple::{Normal3, Point3};
+use std::fmt::Debug;
+
+// a surface defined in its own object space, placed in the world by its
+// transform. Shapes from other crates are traced through
+// `Intersectable::Shape`, the built-in ones have their own variants.
+pub trait Shape<T: Float = f64>: Debug + Send + Sync {
+    // the distances along a ray already brought into object space
+    fn local_intersect(&self, ray: &Ray<T>) -> Vec<T>;
+
+    // the normal at a point of the surface in object space, it's normalized
+    // once in world space
+    fn local_normal_at(&self, point: Point3<T>) -> Normal3<T>;
+
+    // in object space
+    fn bounds(&self) -> BoundingBox<T>;
+
+    fn material(&self) -> Material<T>;
+
+    // the transform at rest, which the default `normal_at` and the
+    // intersections through `Intersectable::Shape` use for every ray. It has
+    // no time, so a shape moving during the shutter interval must not be
+    // traced through it: Sphere freezes itself at the time of each ray
+    // instead, and its `world_bounds` covers the whole motion.
+    fn transform(&self) -> Transform<T>;
+
+    fn world_bounds(&self) -> BoundingBox<T> {
+        self.bounds().transform(&self.transform().matrix())
+    }
+
+    fn normal_at(&self, world_point: Point3<T>) -> Normal3<T> {
+        let transform = self.transform();
+        let local_normal = self.local_normal_at(transform.inverse() * world_point);
+        (transform * local_normal).normalized()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shape;
+    use crate::geo::{BoundingBox, Intersectable, Ray, RayPacket4};
+    use crate::matrix::{Matrix4, Transform};
+    use crate::scene::Material;
+    use crate::tuple::{Normal3, Point3, Vector3};
+
+    // a square of side 2 lying on the xz plane
+    #[derive(Debug)]
+    struct Square {
+        transform: Transform,
+        material: Material,
+    }
+
+    impl Shape for Square {
+        fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+            if ray.direction.y.abs() < f64::EPSILON {
+                return vec![];
+            }
+            let t = -ray.origin.y / ray.direction.y;
+            let point = ray.position(t);
+            if point.x.abs() <= 1. && point.z.abs() <= 1. {
+                vec![t]
+            } else {
+                vec![]
+            }
+        }
+
+        fn local_normal_at(&self, _point: Point3) -> Normal3 {
+            Normal3::normal(0., 1., 0.)
+        }
+
+        fn bounds(&self) -> BoundingBox {
+            BoundingBox::new(Point3::point(-1., 0., -1.), Point3::point(1., 0., 1.))
+        }
+
+        fn material(&self) -> Material {
+            self.material
+        }
+
+        fn transform(&self) -> Transform {
+            self.transform
+        }
+    }
+
+    fn square() -> Intersectable {
+        let material = Material {
+            shininess: 10.,
+            ..Material::default()
+        };
+        Intersectable::shape(Square {
+            // tilted toward -z and raised
+            transform: Transform::new(
+                Matrix4::scale_matrix(2., 1., 2.)
+                    .rotate_x(-0.3)
+                    .translate(0., 1., 0.),
+            ),
+            material,
+        })
+    }
+
+    #[test]
+    fn it_intersects_shapes_defined_outside_of_the_crate() {
+        let square = square();
+        let ray = Ray::new(Point3::point(0.5, 5., 0.5), Vector3::vector(0., -1., 0.));
+        let xs = ray.intersect(&square);
+        assert_eq!(xs.count(), 1);
+        assert_eq!(xs[0].intersectable, square);
+        // the hit lies on the tilted square
+        let point = ray.position(xs[0].t);
+        assert_abs_diff_eq!(point.y, 1. + 0.5 * 0.3_f64.tan(), epsilon = 1e-12);
+        let miss = Ray::new(Point3::point(3., 5., 0.), Vector3::vector(0., -1., 0.));
+        assert_eq!(miss.intersect(&square).count(), 0);
+    }
+
+    #[test]
+    fn it_computes_the_normal_in_world_space() {
+        let square = square();
+        let normal = square.normal_at(Point3::point(0., 1., 0.));
+        assert_abs_diff_eq!(
+            normal,
+            Normal3::normal(0., 0.3_f64.cos(), -0.3_f64.sin()),
+            epsilon = 1e-12
+        );
+        assert_eq!(square.get_material().shininess, 10.);
+    }
+
+    #[test]
+    fn it_bounds_the_shape_in_world_space() {
+        let bounds = square().world_bounds();
+        assert_abs_diff_eq!(bounds.min.x, -2.);
+        assert_abs_diff_eq!(bounds.max.x, 2.);
+        assert_abs_diff_eq!(
+            bounds.max.y - bounds.min.y,
+            4. * 0.3_f64.sin(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_intersects_packets_one_lane_at_a_time() {
+        let square = square();
+        let rays = [-3., -1., 1., 3.]
+            .map(|x| Ray::new(Point3::point(x, 5., 0.), Vector3::vector(0., -1., 0.)));
+        let packet = square.intersections_packet(&RayPacket4::new(rays));
+        assert_eq!(packet.map(|xs| xs.count()), [0, 1, 1, 0]);
+    }
+}
@@ -1,25 +1,29 @@
-use crate::geo::Ray;
+use crate::float::Float;
+use crate::geo::{BoundingBox, Ray, RayPacket, Shape};
 use crate::geo::{Intersectable, Intersection, Intersections};
-use crate::matrix::Matrix4;
+use crate::matrix::{AnimatedTransform, Transform};
 use crate::scene::Material;
-use crate::tuple::{Point3, Vector3};
+use crate::tuple::{Normal3, Point3, Vector3};
 use approx::AbsDiffEq;
 use std::any::Any;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Sphere {
-    pub origin: Point3,
-    pub radius: f64,
-    pub transform: Matrix4, // put it in intersectable?
-    pub material: Material,
+pub struct Sphere<T = f64> {
+    pub origin: Point3<T>,
+    pub radius: T,
+    pub transform: Transform<T>, // put it in intersectable?
+    // overrides `transform` for a sphere moving during the shutter interval
+    pub motion: Option<AnimatedTransform<T>>,
+    pub material: Material<T>,
 }
 
-impl Sphere {
-    pub fn new(origin: Point3, radius: f64) -> Self {
+impl<T: Float> Sphere<T> {
+    pub fn new(origin: Point3<T>, radius: T) -> Self {
         Self {
             origin,
             radius,
-            transform: Matrix4::identity(),
+            transform: Transform::identity(),
+            motion: None,
             material: Material::default(),
         }
     }
@@ -27,57 +31,155 @@ impl Sphere {
     pub fn unit() -> Self {
         Self {
             origin: Point3::origin(),
-            radius: 1.0,
-            transform: Matrix4::identity(),
+            radius: T::ONE,
+            transform: Transform::identity(),
+            motion: None,
             material: Material::default(),
         }
     }
 
-    pub fn intersections(&self, ray: &Ray) -> Intersections {
+    // the sphere frozen at `time`, so that normals computed on the
+    // intersections match the position it was hit at
+    pub fn at_time(&self, time: T) -> Self {
+        match self.motion {
+            Some(motion) => Self {
+                transform: motion.transform_at(time),
+                motion: None,
+                ..*self
+            },
+            None => *self,
+        }
+    }
+
+    pub fn intersections(&self, ray: &Ray<T>) -> Intersections<T> {
         // println!("Sphere:{:?}", self);
         // println!("Ray:{:?}", ray);
-        let transformed_ray = ray.transform(&self.transform.inversed());
-        let sphere_to_ray: Vector3 = transformed_ray.origin - self.origin;
-        let a = transformed_ray.direction.dot(transformed_ray.direction);
-        let b = transformed_ray.direction.dot(sphere_to_ray) * 2.;
-        let c = sphere_to_ray.dot(sphere_to_ray) - 1.;
-        let d = b * b - 4. * a * c;
-        Intersections::new(if d < 0. {
-            vec![]
-        } else {
-            vec![
-                Intersection::new((-b - d.sqrt()) / (2. * a), Intersectable::Sphere(*self)),
-                Intersection::new((-b + d.sqrt()) / (2. * a), Intersectable::Sphere(*self)),
-            ]
+        let sphere = self.at_time(ray.time);
+        let transformed_ray = ray.transform(&sphere.transform.inverse());
+        Intersections::new(match sphere.roots(&transformed_ray) {
+            Some((t1, t2)) => vec![
+                Intersection::new(t1, Intersectable::Sphere(sphere)),
+                Intersection::new(t2, Intersectable::Sphere(sphere)),
+            ],
+            None => vec![],
+        })
+    }
+
+    // the distances to the unit sphere around `origin` along a ray in object
+    // space, `radius` isn't used
+    fn roots(&self, ray: &Ray<T>) -> Option<(T, T)> {
+        let (two, four) = (T::from_f64(2.), T::from_f64(4.));
+        let sphere_to_ray: Vector3<T> = ray.origin - self.origin;
+        let a = ray.direction.dot(ray.direction);
+        let b = ray.direction.dot(sphere_to_ray) * two;
+        let c = sphere_to_ray.dot(sphere_to_ray) - T::ONE;
+        let d = b * b - four * a * c;
+        if d < T::ZERO {
+            return None;
+        }
+        Some(((-b - d.sqrt()) / (two * a), (-b + d.sqrt()) / (two * a)))
+    }
+
+    // the intersections of every lane of the packet, in the same order as
+    // `intersections` gives them for each ray
+    pub fn intersections_packet<const N: usize>(
+        &self,
+        packet: &RayPacket<N, T>,
+    ) -> [Intersections<T>; N] {
+        if self.motion.is_some() {
+            // every lane would need the sphere at its own time
+            return packet.rays().map(|ray| self.intersections(&ray));
+        }
+        let transformed = packet.transform(&self.transform.inverse());
+        let [ox, oy, oz] = transformed.origins;
+        let [dx, dy, dz] = transformed.directions;
+        let (two, four) = (T::from_f64(2.), T::from_f64(4.));
+        let (mut a, mut b, mut d) = ([T::ZERO; N], [T::ZERO; N], [T::ZERO; N]);
+        for lane in 0..N {
+            let sphere_to_ray = [
+                ox[lane] - self.origin.x,
+                oy[lane] - self.origin.y,
+                oz[lane] - self.origin.z,
+            ];
+            a[lane] = dx[lane] * dx[lane] + dy[lane] * dy[lane] + dz[lane] * dz[lane];
+            b[lane] = (dx[lane] * sphere_to_ray[0]
+                + dy[lane] * sphere_to_ray[1]
+                + dz[lane] * sphere_to_ray[2])
+                * two;
+            let c = sphere_to_ray[0] * sphere_to_ray[0]
+                + sphere_to_ray[1] * sphere_to_ray[1]
+                + sphere_to_ray[2] * sphere_to_ray[2]
+                - T::ONE;
+            d[lane] = b[lane] * b[lane] - four * a[lane] * c;
+        }
+        std::array::from_fn(|lane| {
+            let (a, b, d) = (a[lane], b[lane], d[lane]);
+            Intersections::new(if d < T::ZERO {
+                vec![]
+            } else {
+                vec![
+                    Intersection::new((-b - d.sqrt()) / (two * a), Intersectable::Sphere(*self)),
+                    Intersection::new((-b + d.sqrt()) / (two * a), Intersectable::Sphere(*self)),
+                ]
+            })
         })
     }
 
-    pub fn normal_at(&self, world_point: Point3) -> Vector3 {
+    pub fn normal_at(&self, world_point: Point3<T>) -> Normal3<T> {
         // transform the point to the sphere local coordinate system
-        let object_point = self.transform.inversed() * world_point;
+        let object_point = self.transform.inverse() * world_point;
         // calculate the local coordinate system normal
-        let object_normal = object_point - Point3::point(0., 0., 0.);
+        let object_normal = Normal3::from(object_point - Point3::origin());
         // transform the local coordinate system normal to the world coordinate system
-
-        let world_normal =
-            self.transform.inversed().transposed() * object_normal 
-            // force w to be 0 in case of translation
-            * Vector3::vector(1., 1., 1.);
+        let world_normal = self.transform * object_normal;
 
         world_normal.normalized()
     }
 }
 
-impl AbsDiffEq for Sphere {
-    type Epsilon = f64;
+impl<T: Float> Shape<T> for Sphere<T> {
+    fn local_intersect(&self, ray: &Ray<T>) -> Vec<T> {
+        self.roots(ray).map_or(vec![], |(t1, t2)| vec![t1, t2])
+    }
+
+    fn local_normal_at(&self, point: Point3<T>) -> Normal3<T> {
+        Normal3::from(point - Point3::origin())
+    }
+
+    fn bounds(&self) -> BoundingBox<T> {
+        let one = Vector3::vector(T::ONE, T::ONE, T::ONE);
+        BoundingBox::new(self.origin - one, self.origin + one)
+    }
+
+    fn material(&self) -> Material<T> {
+        self.material
+    }
+
+    // ignores `motion`, the intersections freeze the sphere at the time of
+    // the ray, see `at_time`
+    fn transform(&self) -> Transform<T> {
+        self.transform
+    }
+
+    // a moving sphere is bounded over its whole motion
+    fn world_bounds(&self) -> BoundingBox<T> {
+        match self.motion {
+            Some(motion) => self.bounds().transform_animated(&motion),
+            None => self.bounds().transform(&self.transform.matrix()),
+        }
+    }
+}
+
+impl<T: Float> AbsDiffEq for Sphere<T> {
+    type Epsilon = T;
 
     fn default_epsilon() -> Self::Epsilon {
-        f64::EPSILON
+        T::EPSILON
     }
 
-    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
         Point3::abs_diff_eq(&self.origin, &other.origin, epsilon)
-            && f64::abs_diff_eq(&self.radius, &other.radius, epsilon)
+            && T::abs_diff_eq(&self.radius, &other.radius, epsilon)
     }
 }
 
@@ -86,9 +188,9 @@ mod tests {
     use std::f64::consts::PI;
 
     use super::Sphere;
-    use crate::geo::{Intersectable, Ray};
-    use crate::matrix::Matrix4;
-    use crate::tuple::{Point3, Vector3};
+    use crate::geo::{BoundingBox, Intersectable, Ray, RayPacket4, RayPacket8, Shape};
+    use crate::matrix::{AnimatedTransform, Matrix4, Transform};
+    use crate::tuple::{Normal3, Point3, Vector3};
     use crate::scene::Material;
 
     #[test]
@@ -98,23 +200,24 @@ mod tests {
         let i = Intersectable::Sphere(s);
         let xs = r.intersect(&i);
         assert_abs_diff_eq!(xs.count(), 2);
-        let Intersectable::Sphere(s1) = xs[0].intersectable;
+        assert_eq!(xs[0].intersectable, Intersectable::Sphere(s));
+        let Intersectable::Sphere(s1) = xs[0].intersectable else { unreachable!() };
         assert_abs_diff_eq!(s, s1);
-        let Intersectable::Sphere(s2) = xs[1].intersectable;
+        let Intersectable::Sphere(s2) = xs[1].intersectable else { unreachable!() };
         assert_abs_diff_eq!(s, s2);
     }
 
     #[test]
     fn it_has_a_default_transformation() {
-        let s = Sphere::unit();
-        assert_abs_diff_eq!(s.transform, Matrix4::identity());
+        let s: Sphere = Sphere::unit();
+        assert_abs_diff_eq!(s.transform.matrix(), Matrix4::identity());
     }
 
     #[test]
     fn it_can_intersect_with_a_ray_when_scaled() {
         let r = Ray::new(Point3::point(0., 0., -5.), Vector3::vector(0., 0., 1.));
         let mut s = Sphere::unit();
-        s.transform = Matrix4::scale_matrix(2., 2., 2.);
+        s.transform = Transform::new(Matrix4::scale_matrix(2., 2., 2.));
         let xs = s.intersections(&r);
         assert_abs_diff_eq!(xs.count(), 2);
         assert_abs_diff_eq!(xs[0].t, 3.);
@@ -125,30 +228,53 @@ mod tests {
     fn it_can_intersect_with_a_ray_when_translated() {
         let r = Ray::new(Point3::point(0., 0., -5.), Vector3::vector(0., 0., 1.));
         let mut s = Sphere::unit();
-        s.transform = Matrix4::translation_matrix(5., 0., 0.);
+        s.transform = Transform::new(Matrix4::translation_matrix(5., 0., 0.));
         let xs = s.intersections(&r);
         assert_abs_diff_eq!(xs.count(), 0);
     }
 
+    #[test]
+    fn it_intersects_a_moving_sphere_where_it_is_at_the_ray_time() {
+        let mut s = Sphere::unit();
+        s.motion = Some(AnimatedTransform::new(
+            Matrix4::identity(),
+            Matrix4::translation_matrix(4., 0., 0.),
+            0.,
+            1.,
+        ));
+        let direction = Vector3::vector(0., 0., 1.);
+        let r = Ray::with_time(Point3::point(4., 0., -5.), direction, 0.);
+        assert_abs_diff_eq!(s.intersections(&r).count(), 0);
+        let r = Ray::with_time(Point3::point(4., 0., -5.), direction, 1.);
+        let xs = s.intersections(&r);
+        assert_abs_diff_eq!(xs.count(), 2);
+        assert_abs_diff_eq!(xs[0].t, 4.);
+        // the hit sphere is frozen where it was when the ray hit it
+        let n = xs[0].intersectable.normal_at(r.position(xs[0].t));
+        assert_abs_diff_eq!(n, Normal3::normal(0., 0., -1.));
+        let r = Ray::with_time(Point3::point(2., 0., -5.), direction, 0.5);
+        assert_abs_diff_eq!(s.intersections(&r)[0].t, 4.);
+    }
+
     #[test]
     fn it_calculates_a_normal_at_a_point_on_the_x_axis() {
         let s = Sphere::unit();
         let n = s.normal_at(Point3::point(1., 0., 0.));
-        assert_abs_diff_eq!(n, Vector3::vector(1., 0., 0.));
+        assert_abs_diff_eq!(n, Normal3::normal(1., 0., 0.));
     }
 
     #[test]
     fn it_calculates_a_normal_at_a_point_on_the_y_axis() {
         let s = Sphere::unit();
         let n = s.normal_at(Point3::point(0., 1., 0.));
-        assert_abs_diff_eq!(n, Vector3::vector(0., 1., 0.));
+        assert_abs_diff_eq!(n, Normal3::normal(0., 1., 0.));
     }
 
     #[test]
     fn it_calculates_a_normal_at_a_point_on_the_z_axis() {
         let s = Sphere::unit();
         let n = s.normal_at(Point3::point(0., 0., 1.));
-        assert_abs_diff_eq!(n, Vector3::vector(0., 0., 1.));
+        assert_abs_diff_eq!(n, Normal3::normal(0., 0., 1.));
     }
 
     #[test]
@@ -156,34 +282,36 @@ mod tests {
         let s = Sphere::unit();
         let v = f64::sqrt(3.) / 3.;
         let n = s.normal_at(Point3::point(v, v, v));
-        assert_abs_diff_eq!(n, Vector3::vector(v, v, v));
+        assert_abs_diff_eq!(n, Normal3::normal(v, v, v));
     }
 
     #[test]
     fn it_calculates_a_normal_for_a_translated_sphere() {
         let mut s = Sphere::unit();
-        s.transform = Matrix4::translation_matrix(0., 1., 0.);
+        s.transform = Transform::new(Matrix4::translation_matrix(0., 1., 0.));
         let v = f64::sqrt(2.) / 2.;
         let n = s.normal_at(Point3::point(0., 1.0 + v, -v));
-        let expected = Vector3::vector(0., v, -v);
+        let expected = Normal3::normal(0., v, -v);
         assert_abs_diff_eq!(n, expected);
     }
 
     #[test]
     fn it_calculates_a_normal_for_a_transformed_sphere() {
         let mut s = Sphere::unit();
-        s.transform = Matrix4::scale_matrix(1., 0.5, 1.) * Matrix4::rotation_z_matrix(PI / 5.);
+        s.transform = Transform::new(
+            Matrix4::scale_matrix(1., 0.5, 1.) * Matrix4::rotation_z_matrix(PI / 5.),
+        );
         let v = f64::sqrt(2.) / 2.;
         let n = s.normal_at(Point3::point(0., v, -v));
         assert_abs_diff_eq!(
             n,
-            Vector3::vector(0., 0.9701425001453319, -0.24253562503633294)
+            Normal3::normal(0., 0.9701425001453319, -0.24253562503633294)
         );
     }
 
     #[test]
     fn it_has_a_default_material() {
-        let s = Sphere::unit();
+        let s: Sphere = Sphere::unit();
         
         assert_eq!(
             s.material,
@@ -193,7 +321,7 @@ mod tests {
 
     #[test]
     fn it_can_be_assigned_a_new_material() {
-        let mut s = Sphere::unit();
+        let mut s: Sphere = Sphere::unit();
         let m = Material::default();
         s.material = m;
         assert_eq!(
@@ -202,5 +330,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_intersects_packets_like_single_rays() {
+        let mut s = Sphere::unit();
+        s.transform = Transform::new(Matrix4::scale_matrix(2., 1., 1.).translate(0.5, 0., 0.));
+        let rays = [-3., -1.2, -0.4, 0., 0.7, 1.5, 2.4, 4.]
+            .map(|x| Ray::new(Point3::point(x, 0.3, -5.), Vector3::vector(0., 0., 1.)));
+        let packet = s.intersections_packet(&RayPacket8::new(rays));
+        for (ray, xs) in rays.iter().zip(&packet) {
+            let expected = s.intersections(ray);
+            assert_eq!(xs.count(), expected.count());
+            for i in 0..xs.count() {
+                assert_abs_diff_eq!(xs[i].t, expected[i].t, epsilon = 1e-12);
+            }
+        }
+        assert_eq!(packet.iter().filter(|xs| xs.hit().is_some()).count(), 6);
+    }
+
+    #[test]
+    fn it_intersects_packets_at_the_time_of_each_lane() {
+        let mut s = Sphere::unit();
+        s.motion = Some(AnimatedTransform::new(
+            Matrix4::identity(),
+            Matrix4::translation_matrix(3., 0., 0.),
+            0.,
+            1.,
+        ));
+        let (origin, direction) = (Point3::point(3., 0., -5.), Vector3::vector(0., 0., 1.));
+        let rays = [0., 0.5, 0.75, 1.].map(|time| Ray::with_time(origin, direction, time));
+        let packet = s.intersections_packet(&RayPacket4::new(rays));
+        let hits = packet.map(|xs| xs.hit().is_some());
+        assert_eq!(hits, [false, false, true, true]);
+    }
+
+    #[test]
+    fn it_is_bounded_by_its_transformed_box() {
+        let mut s = Sphere::unit();
+        s.transform = Transform::new(Matrix4::scale_matrix(2., 1., 1.).translate(0., 3., 0.));
+        assert_abs_diff_eq!(
+            s.world_bounds(),
+            BoundingBox::new(Point3::point(-2., 2., -1.), Point3::point(2., 4., 1.))
+        );
+        s.motion = Some(AnimatedTransform::new(
+            Matrix4::identity(),
+            Matrix4::translation_matrix(3., 0., 0.),
+            0.,
+            1.,
+        ));
+        assert_abs_diff_eq!(
+            s.world_bounds(),
+            BoundingBox::new(Point3::point(-1., -1., -1.), Point3::point(4., 1., 1.)),
+            epsilon = 1e-12
+        );
+        // half way through a half turn the sphere is outside of both ends
+        let mut s = Sphere::new(Point3::point(3., 0., 0.), 1.);
+        s.motion = Some(AnimatedTransform::new(
+            Matrix4::identity(),
+            Matrix4::rotation_y_matrix(PI),
+            0.,
+            1.,
+        ));
+        let bounds = s.world_bounds();
+        for time in [0., 0.25, 0.5, 0.75, 1.] {
+            let center = s.at_time(time).transform * s.origin;
+            assert!(bounds.contains(center + Vector3::vector(0., 0., 1.)));
+            assert!(bounds.contains(center - Vector3::vector(0., 0., 1.)));
+        }
+    }
+
 
 }
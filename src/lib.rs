@@ -0,0 +1,16 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+// #![feature(generic_associated_types)]
+
+#[macro_use]
+extern crate approx;
+pub mod canvas;
+pub mod color;
+pub mod float;
+mod simd;
+pub mod matrix;
+pub mod tuple;
+pub mod geo;
+pub mod scene;
+pub mod render;
+pub mod putting_it_together;
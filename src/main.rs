@@ -1,24 +1,11 @@
-#![allow(dead_code)]
-#![allow(unused_imports)]
-// #![feature(generic_associated_types)]
-
-#[macro_use]
-extern crate approx;
-mod canvas;
-mod color;
-mod matrix;
-mod tuple;
-mod geo;
-mod scene;
-mod putting_it_together;
-
+use scintilla::putting_it_together;
 
 fn main() {
     // putting_it_together::putting_it_together1::run()
     // putting_it_together::putting_it_together2::run()
     // putting_it_together::putting_it_together3::run()
     // putting_it_together::putting_it_together4::run()
-    putting_it_together::putting_it_together5::run()
+    // putting_it_together::putting_it_together5::run()
+    // putting_it_together::putting_it_together6::run()
+    putting_it_together::putting_it_together7::run()
 }
-
-
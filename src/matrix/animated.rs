@@ -0,0 +1,110 @@
+use crate::float::Float;
+use crate::matrix::{Decomposition, Matrix4, Transform};
+
+// a transform moving from `start` to `end` between two points in time
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AnimatedTransform<T = f64> {
+    start: Matrix4<T>,
+    end: Matrix4<T>,
+    start_time: T,
+    end_time: T,
+    // computed once, every ray asks for the transform at its own time
+    decomposed: (Decomposition<T>, Decomposition<T>),
+    transforms: (Transform<T>, Transform<T>),
+}
+
+impl<T: Float> AnimatedTransform<T> {
+    pub fn new(start: Matrix4<T>, end: Matrix4<T>, start_time: T, end_time: T) -> Self {
+        Self {
+            start,
+            end,
+            start_time,
+            end_time,
+            decomposed: (start.decompose(), end.decompose()),
+            transforms: (Transform::new(start), Transform::new(end)),
+        }
+    }
+
+    pub fn start(&self) -> Matrix4<T> {
+        self.start
+    }
+
+    pub fn end(&self) -> Matrix4<T> {
+        self.end
+    }
+
+    // the transform is held constant outside of [start_time, end_time]
+    pub fn at(&self, time: T) -> Matrix4<T> {
+        if time <= self.start_time || self.start == self.end {
+            return self.start;
+        }
+        if time >= self.end_time {
+            return self.end;
+        }
+        self.interpolated(time).to_matrix()
+    }
+
+    // `at` with the inverse, interpolated rather than inverted for every ray
+    pub fn transform_at(&self, time: T) -> Transform<T> {
+        if time <= self.start_time || self.start == self.end {
+            return self.transforms.0;
+        }
+        if time >= self.end_time {
+            return self.transforms.1;
+        }
+        let (matrix, inverse) = self.interpolated(time).to_matrices();
+        Transform::with_inverse(matrix, inverse)
+    }
+
+    fn interpolated(&self, time: T) -> Decomposition<T> {
+        let t = (time - self.start_time) / (self.end_time - self.start_time);
+        let (start, end) = &self.decomposed;
+        start.interpolate(end, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::AnimatedTransform;
+    use crate::matrix::Matrix4;
+    use crate::tuple::Point3;
+
+    #[test]
+    fn it_returns_the_keyframes_at_the_ends_of_the_interval() {
+        let start = Matrix4::translation_matrix(1., 0., 0.);
+        let end = Matrix4::rotation_x_matrix(1.).scale(2., 2., 2.);
+        let animated = AnimatedTransform::new(start, end, 1., 3.);
+        assert_eq!(animated.at(0.), start);
+        assert_eq!(animated.at(1.), start);
+        assert_eq!(animated.at(3.), end);
+        assert_eq!(animated.at(10.), end);
+    }
+
+    #[test]
+    fn it_interpolates_the_inverse_along_with_the_transform() {
+        let start = Matrix4::translation_matrix(1., 0., 0.);
+        let end = Matrix4::rotation_x_matrix(1.).scale(2., 3., 1.);
+        let animated = AnimatedTransform::new(start, end, 0., 1.);
+        for time in [-1., 0.25, 0.5, 0.9, 2.] {
+            let transform = animated.transform_at(time);
+            assert_abs_diff_eq!(transform.matrix(), animated.at(time), epsilon = 1e-12);
+            assert_abs_diff_eq!(
+                transform.inverse(),
+                animated.at(time).inversed(),
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn it_keeps_rotations_rigid_in_between() {
+        let start = Matrix4::identity();
+        let end = Matrix4::rotation_z_matrix(PI);
+        let animated = AnimatedTransform::new(start, end, 0., 1.);
+        // a naive lerp of the matrices would collapse the point on the origin
+        let point = animated.at(0.5) * Point3::point(1., 0., 0.);
+        assert_abs_diff_eq!(point, Point3::point(0., 1., 0.), epsilon = 1e-12);
+    }
+}
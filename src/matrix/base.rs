@@ -1,41 +1,42 @@
-use crate::tuple::Tuple;
+use crate::float::Float;
+use crate::tuple::{Point3, Tuple, Vector3};
 use approx::AbsDiffEq;
 use std::fmt;
 use std::ops::{Index, IndexMut, Mul};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Matrix<const W: usize, const H: usize> {
-    pub(in crate::matrix) data: [[f64; W]; H],
+pub struct Matrix<const W: usize, const H: usize, T = f64> {
+    pub(in crate::matrix) data: [[T; W]; H],
 }
 
-pub type Matrix2 = Matrix<2, 2>;
-pub type Matrix3 = Matrix<3, 3>;
-pub type Matrix4 = Matrix<4, 4>;
+pub type Matrix2<T = f64> = Matrix<2, 2, T>;
+pub type Matrix3<T = f64> = Matrix<3, 3, T>;
+pub type Matrix4<T = f64> = Matrix<4, 4, T>;
 
-impl<const W: usize, const H: usize> Default for Matrix<W, H> {
+impl<const W: usize, const H: usize, T: Float> Default for Matrix<W, H, T> {
     fn default() -> Self {
-        Matrix::<W, H>::from_rows([[0.0; W]; H])
+        Matrix::<W, H, T>::from_rows([[T::ZERO; W]; H])
     }
 }
 
-impl<const W: usize, const H: usize> Matrix<W, H> {
+impl<const W: usize, const H: usize, T: Float> Matrix<W, H, T> {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn from_rows(rows: [[f64; W]; H]) -> Self {
+    pub fn from_rows(rows: [[T; W]; H]) -> Self {
         Self { data: rows }
     }
 
-    pub fn from_tuple(tuple: Tuple) -> Matrix<1, 4> {
-        Matrix::<1, 4> {
+    pub fn from_tuple(tuple: Tuple<T>) -> Matrix<1, 4, T> {
+        Matrix::<1, 4, T> {
             data: [[tuple.x], [tuple.y], [tuple.z], [tuple.w]],
         }
     }
 }
 
-impl<const W: usize, const H: usize> Index<(usize, usize)> for Matrix<W, H> {
-    type Output = f64;
+impl<const W: usize, const H: usize, T: Float> Index<(usize, usize)> for Matrix<W, H, T> {
+    type Output = T;
 
     // (row, col)
     fn index(&self, indice: (usize, usize)) -> &Self::Output {
@@ -43,23 +44,22 @@ impl<const W: usize, const H: usize> Index<(usize, usize)> for Matrix<W, H> {
     }
 }
 
-impl<const W: usize, const H: usize> IndexMut<(usize, usize)> for Matrix<W, H> {
+impl<const W: usize, const H: usize, T: Float> IndexMut<(usize, usize)> for Matrix<W, H, T> {
     // (row, col)
     fn index_mut(&mut self, indice: (usize, usize)) -> &mut Self::Output {
         &mut self.data[indice.0][indice.1]
     }
 }
 
-impl<const W: usize, const H: usize, const OW: usize, const OH: usize> Mul<Matrix<OW, OH>>
-    for Matrix<W, H>
-{
-    type Output = Matrix<OW, H>;
-
-    fn mul(self, other: Matrix<OW, OH>) -> Matrix<OW, H> {
-        let mut matrix = Matrix::<OW, H>::default();
+impl<const W: usize, const H: usize, T: Float> Matrix<W, H, T> {
+    fn product<const OW: usize, const OH: usize>(
+        &self,
+        other: &Matrix<OW, OH, T>,
+    ) -> Matrix<OW, H, T> {
+        let mut matrix = Matrix::<OW, H, T>::default();
         for row in 0..H {
             for col in 0..OW {
-                let mut value = 0.0;
+                let mut value = T::ZERO;
                 for i in 0..W {
                     value += self[(row, i)] * other[(i, col)]
                 }
@@ -71,76 +71,104 @@ impl<const W: usize, const H: usize, const OW: usize, const OH: usize> Mul<Matri
     }
 }
 
-impl<const W: usize, const H: usize, const OW: usize, const OH: usize> Mul<Matrix<OW, OH>>
-    for &Matrix<W, H>
+impl<const W: usize, const H: usize, const OW: usize, const OH: usize, T: Float>
+    Mul<Matrix<OW, OH, T>> for Matrix<W, H, T>
 {
-    type Output = Matrix<OW, H>;
+    type Output = Matrix<OW, H, T>;
 
-    fn mul(self, other: Matrix<OW, OH>) -> Matrix<OW, H> {
-        let mut matrix = Matrix::<OW, H>::default();
-        for row in 0..H {
-            for col in 0..OW {
-                let mut value = 0.0;
-                for i in 0..W {
-                    value += self[(row, i)] * other[(i, col)]
-                }
-                matrix[(row, col)] = value
-            }
-        }
+    fn mul(self, other: Matrix<OW, OH, T>) -> Matrix<OW, H, T> {
+        self.product(&other)
+    }
+}
 
-        matrix
+impl<const W: usize, const H: usize, const OW: usize, const OH: usize, T: Float>
+    Mul<Matrix<OW, OH, T>> for &Matrix<W, H, T>
+{
+    type Output = Matrix<OW, H, T>;
+
+    fn mul(self, other: Matrix<OW, OH, T>) -> Matrix<OW, H, T> {
+        self.product(&other)
     }
 }
 
-impl<const W: usize, const H: usize, const OW: usize, const OH: usize> Mul<&Matrix<OW, OH>>
-    for Matrix<W, H>
+impl<const W: usize, const H: usize, const OW: usize, const OH: usize, T: Float>
+    Mul<&Matrix<OW, OH, T>> for Matrix<W, H, T>
 {
-    type Output = Matrix<OW, H>;
+    type Output = Matrix<OW, H, T>;
 
-    fn mul(self, other: &Matrix<OW, OH>) -> Matrix<OW, H> {
-        let mut matrix = Matrix::<OW, H>::default();
-        for row in 0..H {
-            for col in 0..OW {
-                let mut value = 0.0;
-                for i in 0..W {
-                    value += self[(row, i)] * other[(i, col)]
-                }
-                matrix[(row, col)] = value
-            }
-        }
+    fn mul(self, other: &Matrix<OW, OH, T>) -> Matrix<OW, H, T> {
+        self.product(other)
+    }
+}
 
-        matrix
+impl<T: Float> Mul<Tuple<T>> for Matrix<4, 4, T> {
+    type Output = Tuple<T>;
+
+    fn mul(self, other: Tuple<T>) -> Tuple<T> {
+        Tuple::from(T::mul_matrix4_tuple(&self.data, other.into()))
     }
 }
 
-impl Mul<Tuple> for Matrix<4, 4> {
-    type Output = Tuple;
+impl<T: Float> Mul<Tuple<T>> for &Matrix<4, 4, T> {
+    type Output = Tuple<T>;
 
-    fn mul(self, other: Tuple) -> Tuple {
-        let other_matrix = Matrix::<4,4>::from_tuple(other);
-        let res = self * other_matrix;
-        Tuple::new(res[(0, 0)], res[(1, 0)], res[(2, 0)], res[(3, 0)])
+    fn mul(self, other: Tuple<T>) -> Tuple<T> {
+        Tuple::from(T::mul_matrix4_tuple(&self.data, other.into()))
     }
 }
 
-impl Mul<Tuple> for &Matrix<4, 4> {
-    type Output = Tuple;
+impl<T: Float> Matrix<4, 4, T> {
+    // the upper 3x3 applied to (x, y, z) plus `w` times the translation
+    fn transform(&self, x: T, y: T, z: T, w: T) -> [T; 3] {
+        let [x, y, z, _] = T::mul_matrix4_tuple(&self.data, [x, y, z, w]);
+        [x, y, z]
+    }
+}
+
+// affine transforms only, the bottom row is assumed to be (0, 0, 0, 1)
+impl<T: Float> Mul<Point3<T>> for Matrix<4, 4, T> {
+    type Output = Point3<T>;
+
+    fn mul(self, other: Point3<T>) -> Point3<T> {
+        &self * other
+    }
+}
+
+impl<T: Float> Mul<Point3<T>> for &Matrix<4, 4, T> {
+    type Output = Point3<T>;
 
-    fn mul(self, other: Tuple) -> Tuple {
-        let other = Matrix::<4,4>::from_tuple(other);
-        let res = self * other;
-        Tuple::new(res[(0, 0)], res[(1, 0)], res[(2, 0)], res[(3, 0)])
+    fn mul(self, other: Point3<T>) -> Point3<T> {
+        let [x, y, z] = self.transform(other.x, other.y, other.z, T::ONE);
+        Point3::point(x, y, z)
     }
 }
 
-impl<const W: usize, const H: usize> AbsDiffEq for Matrix<W, H> {
-    type Epsilon = f64;
+// vectors ignore the translation
+impl<T: Float> Mul<Vector3<T>> for Matrix<4, 4, T> {
+    type Output = Vector3<T>;
+
+    fn mul(self, other: Vector3<T>) -> Vector3<T> {
+        &self * other
+    }
+}
+
+impl<T: Float> Mul<Vector3<T>> for &Matrix<4, 4, T> {
+    type Output = Vector3<T>;
+
+    fn mul(self, other: Vector3<T>) -> Vector3<T> {
+        let [x, y, z] = self.transform(other.x, other.y, other.z, T::ZERO);
+        Vector3::vector(x, y, z)
+    }
+}
+
+impl<const W: usize, const H: usize, T: Float> AbsDiffEq for Matrix<W, H, T> {
+    type Epsilon = T;
 
     fn default_epsilon() -> Self::Epsilon {
-        1.0E-14f64
+        T::APPROX_EPSILON
     }
 
-    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
         self.data
             .iter()
             .zip(other.data.iter())
@@ -148,36 +176,16 @@ impl<const W: usize, const H: usize> AbsDiffEq for Matrix<W, H> {
                 accu && a
                     .iter()
                     .zip(b)
-                    .fold(true, |accu2, (s, t)| accu2 && f64::abs_diff_eq(&s, &t, epsilon))
+                    .fold(true, |accu2, (s, t)| accu2 && T::abs_diff_eq(s, t, epsilon))
             })
     }
 }
 
-impl Matrix4 {
-    pub fn identity() -> Self {
-        let mut res = Matrix4::new();
-        for i in 0..4 {
-            res[(i, i)] = 1.;
-        }
-        res
-    }
-}
-
-impl Matrix3 {
-    pub fn identity() -> Self {
-        let mut res = Matrix3::new();
-        for i in 0..3 {
-            res[(i, i)] = 1.;
-        }
-        res
-    }
-}
-
-impl Matrix2 {
+impl<const N: usize, T: Float> Matrix<N, N, T> {
     pub fn identity() -> Self {
-        let mut res = Matrix2::new();
-        for i in 0..2 {
-            res[(i, i)] = 1.;
+        let mut res = Matrix::<N, N, T>::new();
+        for i in 0..N {
+            res[(i, i)] = T::ONE;
         }
         res
     }
@@ -302,7 +310,7 @@ mod tests {
             [4., 8., 16., 32.],
         ]);
         let identity = Matrix4::identity();
-        let expected = matrix.clone();
+        let expected = matrix;
         assert_abs_diff_eq!(matrix * identity, expected);
     }
 }
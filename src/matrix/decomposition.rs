@@ -0,0 +1,210 @@
+use crate::float::Float;
+use crate::matrix::{Matrix4, Quaternion};
+use crate::tuple::Vector3;
+use approx::AbsDiffEq;
+
+// an affine transform split into translation * rotation * shear * scale
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Decomposition<T = f64> {
+    pub translation: Vector3<T>,
+    pub rotation: Quaternion<T>,
+    // the xy, xz and yz factors of `Matrix4::shear_matrix`, the others are 0
+    pub shear: Vector3<T>,
+    pub scale: Vector3<T>,
+}
+
+impl<T: Float> Decomposition<T> {
+    pub fn to_matrix(self) -> Matrix4<T> {
+        let t = self.translation;
+        let h = self.shear;
+        let s = self.scale;
+        Matrix4::translation_matrix(t.x, t.y, t.z)
+            * self.rotation.to_matrix()
+            * Matrix4::shear_matrix(h.x, h.y, T::ZERO, h.z, T::ZERO, T::ZERO)
+            * Matrix4::scale_matrix(s.x, s.y, s.z)
+    }
+
+    // to_matrix and the inverse, which is put together from the inverted
+    // components instead of inverting the matrix
+    pub fn to_matrices(self) -> (Matrix4<T>, Matrix4<T>) {
+        let (t, h, s) = (self.translation, self.shear, self.scale);
+        let (zero, one) = (T::ZERO, T::ONE);
+        let rotation = self.rotation.to_matrix();
+        // shear * scale and its inverse, both upper triangular
+        let sheared = Matrix4::from_rows([
+            [s.x, h.x * s.y, h.y * s.z, zero],
+            [zero, s.y, h.z * s.z, zero],
+            [zero, zero, s.z, zero],
+            [zero, zero, zero, one],
+        ]);
+        let unsheared = Matrix4::from_rows([
+            [one / s.x, -h.x / s.x, (h.x * h.z - h.y) / s.x, zero],
+            [zero, one / s.y, -h.z / s.y, zero],
+            [zero, zero, one / s.z, zero],
+            [zero, zero, zero, one],
+        ]);
+        let matrix = Matrix4::translation_matrix(t.x, t.y, t.z) * rotation * sheared;
+        let inverse =
+            unsheared * rotation.transposed() * Matrix4::translation_matrix(-t.x, -t.y, -t.z);
+        (matrix, inverse)
+    }
+
+    // interpolate every component on its own so that rotations stay rigid
+    pub fn interpolate(&self, other: &Self, t: T) -> Self {
+        Self {
+            translation: self.translation + (other.translation - self.translation) * t,
+            rotation: self.rotation.slerp(other.rotation, t),
+            shear: self.shear + (other.shear - self.shear) * t,
+            scale: self.scale + (other.scale - self.scale) * t,
+        }
+    }
+}
+
+impl<T: Float> AbsDiffEq for Decomposition<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        Vector3::abs_diff_eq(&self.translation, &other.translation, epsilon)
+            && Quaternion::abs_diff_eq(&self.rotation, &other.rotation, epsilon)
+            && Vector3::abs_diff_eq(&self.shear, &other.shear, epsilon)
+            && Vector3::abs_diff_eq(&self.scale, &other.scale, epsilon)
+    }
+}
+
+impl<T: Float> Matrix4<T> {
+    // Gram-Schmidt on the columns of the upper 3x3, the projections that are
+    // removed on the way are the shear
+    pub fn decompose(&self) -> Decomposition<T> {
+        let column = |col: usize| Vector3::vector(self[(0, col)], self[(1, col)], self[(2, col)]);
+        let translation = Vector3::vector(self[(0, 3)], self[(1, 3)], self[(2, 3)]);
+
+        let mut x_axis = column(0);
+        let mut scale_x = x_axis.magnitude();
+        x_axis = x_axis / scale_x;
+
+        let y_column = column(1);
+        let mut y_axis = y_column - x_axis * x_axis.dot(y_column);
+        let scale_y = y_axis.magnitude();
+        y_axis = y_axis / scale_y;
+
+        let z_column = column(2);
+        let mut z_axis = z_column - x_axis * x_axis.dot(z_column) - y_axis * y_axis.dot(z_column);
+        let scale_z = z_axis.magnitude();
+        z_axis = z_axis / scale_z;
+
+        // a reflection is folded into a negative scale so that the rotation
+        // remains a proper one
+        if x_axis.cross(y_axis).dot(z_axis) < T::ZERO {
+            x_axis = -x_axis;
+            scale_x = -scale_x;
+        }
+        let shear = Vector3::vector(
+            x_axis.dot(y_column) / scale_y,
+            x_axis.dot(z_column) / scale_z,
+            y_axis.dot(z_column) / scale_z,
+        );
+
+        let (zero, one) = (T::ZERO, T::ONE);
+        let rotation = Matrix4::from_rows([
+            [x_axis.x, y_axis.x, z_axis.x, zero],
+            [x_axis.y, y_axis.y, z_axis.y, zero],
+            [x_axis.z, y_axis.z, z_axis.z, zero],
+            [zero, zero, zero, one],
+        ]);
+
+        Decomposition {
+            translation,
+            rotation: Quaternion::from_rotation_matrix(&rotation),
+            shear,
+            scale: Vector3::vector(scale_x, scale_y, scale_z),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::matrix::{Matrix4, Quaternion};
+    use crate::tuple::Vector3;
+
+    #[test]
+    fn it_decomposes_a_translation_rotation_and_scale() {
+        let matrix = Matrix4::identity()
+            .scale(2., 3., 0.5)
+            .rotate_y(PI / 3.)
+            .translate(1., -2., 5.);
+        let decomposition = matrix.decompose();
+        assert_abs_diff_eq!(decomposition.translation, Vector3::vector(1., -2., 5.));
+        assert_abs_diff_eq!(
+            decomposition.scale,
+            Vector3::vector(2., 3., 0.5),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            decomposition.rotation,
+            Quaternion::from_rotation_matrix(&Matrix4::rotation_y_matrix(PI / 3.)),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(decomposition.to_matrix(), matrix, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_inverts_the_components() {
+        let matrix = (Matrix4::shear_matrix(0.2, 0.7, 0., -0.4, 0., 0.)
+            * Matrix4::scale_matrix(2., -3., 0.5))
+        .rotate_x(0.9)
+        .translate(1., -2., 5.);
+        let (composed, inverse) = matrix.decompose().to_matrices();
+        assert_abs_diff_eq!(composed, matrix, epsilon = 1e-12);
+        assert_abs_diff_eq!(inverse, matrix.inversed(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_folds_reflections_into_a_negative_scale() {
+        let matrix = Matrix4::scale_matrix(-1., 1., 1.).rotate_x(0.4);
+        let decomposition = matrix.decompose();
+        assert_abs_diff_eq!(decomposition.scale.x, -1., epsilon = 1e-12);
+        assert_abs_diff_eq!(decomposition.to_matrix(), matrix, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_interpolates_without_shrinking_rotations() {
+        let start = Matrix4::identity().decompose();
+        let end = Matrix4::rotation_z_matrix(PI / 2.)
+            .translate(4., 0., 0.)
+            .decompose();
+        let half = start.interpolate(&end, 0.5).to_matrix();
+        let expected = Matrix4::rotation_z_matrix(PI / 4.).translate(2., 0., 0.);
+        assert_abs_diff_eq!(half, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_extracts_the_shear() {
+        let matrix = (Matrix4::shear_matrix(0.5, -0.25, 0., 1.5, 0., 0.)
+            * Matrix4::scale_matrix(2., 1., 3.))
+        .rotate_x(0.7)
+        .translate(0., 4., 0.);
+        let decomposition = matrix.decompose();
+        assert_abs_diff_eq!(
+            decomposition.shear,
+            Vector3::vector(0.5, -0.25, 1.5),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            decomposition.rotation,
+            Quaternion::from_rotation_matrix(&Matrix4::rotation_x_matrix(0.7)),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(decomposition.to_matrix(), matrix, epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            Matrix4::rotation_y_matrix(1.).decompose().shear,
+            Vector3::vector(0., 0., 0.),
+            epsilon = 1e-12
+        );
+    }
+}
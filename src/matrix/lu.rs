@@ -0,0 +1,350 @@
+use crate::float::Float;
+use crate::matrix::Matrix;
+use std::cmp::Ordering;
+
+// LU decomposition with partial pivoting, the rows of the matrix permuted by
+// `pivots` equal L * U. L has an implicit unit diagonal and is stored below
+// the diagonal of `factors`, U on and above it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lu<const N: usize, T = f64> {
+    factors: Matrix<N, N, T>,
+    pivots: [usize; N],
+    // 1 or -1 depending on the parity of the row swaps
+    sign: T,
+    singular: bool,
+}
+
+// for the rank, a pivot this small relative to the largest entry counts as
+// zero
+fn tolerance<const W: usize, const H: usize, T: Float>(matrix: &Matrix<W, H, T>) -> T {
+    let largest = matrix
+        .data
+        .iter()
+        .flatten()
+        .fold(T::ZERO, |max, value| max.max(value.abs()));
+    largest * T::from_f64(W.max(H) as f64) * T::EPSILON
+}
+
+// the singularity rule of both the decomposition and the closed form 4x4
+// inverse: the determinant divided by the largest entry of every row is
+// this small. Scaling a row scales both, so that a matrix scaling an axis by
+// 1e-12 next to one by 1e6 is still invertible.
+pub(super) fn is_singular<const N: usize, T: Float>(det: T, matrix: &Matrix<N, N, T>) -> bool {
+    let relative = matrix.data.iter().fold(det.abs(), |det, row| {
+        det / row.iter().fold(T::ZERO, |max, value| max.max(value.abs()))
+    });
+    // a row of zeros gives NaN
+    relative.partial_cmp(&(T::from_f64(N as f64) * T::EPSILON)) != Some(Ordering::Greater)
+}
+
+impl<const N: usize, T: Float> Lu<N, T> {
+    pub fn new(matrix: &Matrix<N, N, T>) -> Self {
+        let mut factors = *matrix;
+        let mut pivots = [0; N];
+        pivots.iter_mut().enumerate().for_each(|(i, p)| *p = i);
+        let (mut sign, mut singular) = (T::ONE, N == 0);
+        for col in 0..N {
+            let pivot = (col..N)
+                .max_by(|&a, &b| factors[(a, col)].abs().total_cmp(&factors[(b, col)].abs()))
+                .unwrap();
+            if factors[(pivot, col)] == T::ZERO {
+                // nothing left to eliminate in this column
+                singular = true;
+                continue;
+            }
+            if pivot != col {
+                factors.data.swap(pivot, col);
+                pivots.swap(pivot, col);
+                sign = -sign;
+            }
+            for row in col + 1..N {
+                let factor = factors[(row, col)] / factors[(col, col)];
+                factors[(row, col)] = factor;
+                for c in col + 1..N {
+                    let value = factor * factors[(col, c)];
+                    factors[(row, c)] -= value;
+                }
+            }
+        }
+        let det = (0..N).fold(sign, |det, i| det * factors[(i, i)]);
+        Self {
+            factors,
+            pivots,
+            sign,
+            singular: singular || is_singular(det, matrix),
+        }
+    }
+
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    pub fn det(&self) -> T {
+        if self.singular {
+            return T::ZERO;
+        }
+        (0..N).fold(self.sign, |det, i| det * self.factors[(i, i)])
+    }
+
+    // x such that the matrix times x is `b`
+    pub fn solve(&self, b: &[T; N]) -> Option<[T; N]> {
+        if self.singular {
+            return None;
+        }
+        let mut x = [T::ZERO; N];
+        // forward substitution with L
+        for row in 0..N {
+            x[row] = b[self.pivots[row]];
+            for col in 0..row {
+                x[row] -= self.factors[(row, col)] * x[col];
+            }
+        }
+        // back substitution with U
+        for row in (0..N).rev() {
+            for col in row + 1..N {
+                x[row] -= self.factors[(row, col)] * x[col];
+            }
+            x[row] /= self.factors[(row, row)];
+        }
+        Some(x)
+    }
+
+    // all the columns at once, whole rows at a time so that the updates
+    // vectorize
+    pub fn inverse(&self) -> Option<Matrix<N, N, T>> {
+        if self.singular {
+            return None;
+        }
+        let mut inverse = Matrix::<N, N, T>::new();
+        for (row, pivot) in self.pivots.iter().enumerate() {
+            inverse[(row, *pivot)] = T::ONE;
+        }
+        // forward substitution with L
+        for row in 1..N {
+            for col in 0..row {
+                let factor = self.factors[(row, col)];
+                let source = inverse.data[col];
+                for (value, s) in inverse.data[row].iter_mut().zip(source) {
+                    *value -= factor * s;
+                }
+            }
+        }
+        // back substitution with U
+        for row in (0..N).rev() {
+            for col in row + 1..N {
+                let factor = self.factors[(row, col)];
+                let source = inverse.data[col];
+                for (value, s) in inverse.data[row].iter_mut().zip(source) {
+                    *value -= factor * s;
+                }
+            }
+            let scale = T::ONE / self.factors[(row, row)];
+            for value in inverse.data[row].iter_mut() {
+                *value *= scale;
+            }
+        }
+        Some(inverse)
+    }
+}
+
+impl<const N: usize, T: Float> Matrix<N, N, T> {
+    pub fn lu(&self) -> Lu<N, T> {
+        Lu::new(self)
+    }
+
+    // None instead of infinities and NaNs for singular matrices, see
+    // Matrix4::try_inversed_closed_form for the faster 4x4 one
+    pub fn try_inversed(&self) -> Option<Self> {
+        self.lu().inverse()
+    }
+
+    pub fn solve(&self, b: &[T; N]) -> Option<[T; N]> {
+        self.lu().solve(b)
+    }
+}
+
+impl<const W: usize, const H: usize, T: Float> Matrix<W, H, T> {
+    // the number of linearly independent rows, from the row echelon form
+    pub fn rank(&self) -> usize {
+        let tolerance = tolerance(self);
+        let mut echelon = *self;
+        let mut rank = 0;
+        for col in 0..W {
+            if rank == H {
+                break;
+            }
+            let pivot = (rank..H)
+                .max_by(|&a, &b| echelon[(a, col)].abs().total_cmp(&echelon[(b, col)].abs()))
+                .unwrap();
+            if echelon[(pivot, col)].abs() <= tolerance {
+                continue;
+            }
+            echelon.data.swap(pivot, rank);
+            for row in rank + 1..H {
+                let factor = echelon[(row, col)] / echelon[(rank, col)];
+                for c in col..W {
+                    let value = factor * echelon[(rank, c)];
+                    echelon[(row, c)] -= value;
+                }
+            }
+            rank += 1;
+        }
+        rank
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matrix::{Matrix, Matrix3, Matrix4};
+
+    fn matrix5() -> Matrix<5, 5> {
+        Matrix::from_rows([
+            [2., -1., 0., 3., 1.],
+            [4., 1., -2., 0., 5.],
+            [-3., 2., 7., 1., 0.],
+            [1., 0., 4., -6., 2.],
+            [0., 3., -1., 2., -4.],
+        ])
+    }
+
+    #[test]
+    fn it_matches_the_cofactor_determinants() {
+        let matrix = Matrix4::from_rows([
+            [-2., -8., 3., 5.],
+            [-3., 1., 7., 3.],
+            [1., 2., -9., 6.],
+            [-6., 7., 7., -9.],
+        ]);
+        assert_abs_diff_eq!(matrix.lu().det(), matrix.det(), epsilon = 1e-9);
+        let matrix = Matrix3::from_rows([[1., 2., 6.], [-5., 8., -4.], [2., 6., 4.]]);
+        assert_abs_diff_eq!(matrix.lu().det(), -196., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn it_calculates_the_determinant_of_larger_matrices() {
+        // expanded along the first row with the 4x4 cofactors
+        let matrix = matrix5();
+        let mut expected = 0.;
+        for col in 0..5 {
+            let mut minor = Matrix4::new();
+            for row in 1..5 {
+                for (c, source) in (0..5).filter(|&c| c != col).enumerate() {
+                    minor[(row - 1, c)] = matrix[(row, source)];
+                }
+            }
+            let sign = if col % 2 == 0 { 1. } else { -1. };
+            expected += sign * matrix[(0, col)] * minor.det();
+        }
+        assert_abs_diff_eq!(matrix.lu().det(), expected, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn it_inverts_matrices_of_any_size() {
+        let matrix = matrix5();
+        let inverse = matrix.try_inversed().unwrap();
+        assert_abs_diff_eq!(matrix * inverse, Matrix::identity(), epsilon = 1e-12);
+        assert_abs_diff_eq!(inverse * matrix, Matrix::identity(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_solves_linear_systems() {
+        let matrix = Matrix3::from_rows([[2., 1., -1.], [-3., -1., 2.], [-2., 1., 2.]]);
+        let x = matrix.solve(&[8., -11., -3.]).unwrap();
+        for (value, expected) in x.iter().zip([2., 3., -1.]) {
+            assert_abs_diff_eq!(*value, expected, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn it_refuses_singular_matrices() {
+        let matrix = Matrix4::from_rows([
+            [1., 2., 3., 4.],
+            [2., 4., 6., 8.],
+            [0., 1., 0., 1.],
+            [5., 5., 5., 5.],
+        ]);
+        let lu = matrix.lu();
+        assert!(lu.is_singular());
+        assert_eq!(lu.det(), 0.);
+        assert_eq!(matrix.try_inversed(), None);
+        assert_eq!(matrix.solve(&[1., 2., 3., 4.]), None);
+        assert!(!matrix.is_invertible());
+    }
+
+    #[test]
+    fn it_matches_the_closed_form_inverse_of_4x4_matrices() {
+        let matrix = Matrix4::from_rows([
+            [-5., 2., 6., -8.],
+            [1., -5., 1., 8.],
+            [7., 7., -6., -7.],
+            [1., -3., 7., 4.],
+        ]);
+        let inverse = matrix.try_inversed_closed_form().unwrap();
+        assert_abs_diff_eq!(inverse, matrix.try_inversed().unwrap(), epsilon = 1e-12);
+        assert_abs_diff_eq!(inverse, matrix.inversed());
+    }
+
+    #[test]
+    fn it_agrees_with_the_closed_form_on_nearly_singular_matrices() {
+        // the second row is a combination of the others up to rounding
+        let (a, b) = ([0.3, 0.7, 1.1, 2.9], [1.7, -0.2, 0.9, 0.1]);
+        let row = [0, 1, 2, 3].map(|i| a[i] / 3. + b[i] * 0.7);
+        let matrix = Matrix4::from_rows([a, row, b, [1., 2., 3., 4.]]);
+        assert_eq!(matrix.try_inversed(), None);
+        assert_eq!(matrix.try_inversed_closed_form(), None);
+        assert!(!matrix.is_invertible());
+        // both invert it once the row is nudged off the combination
+        let mut matrix = matrix;
+        matrix[(1, 3)] += 1e-9;
+        assert!(matrix.try_inversed().is_some());
+        assert!(matrix.try_inversed_closed_form().is_some());
+    }
+
+    #[test]
+    fn it_inverts_matrices_scaled_very_differently_along_each_axis() {
+        let matrix = Matrix4::scale_matrix(1e6, 1e-12, 1.);
+        let expected = Matrix4::scale_matrix(1e-6, 1e12, 1.);
+        assert!(matrix.is_invertible());
+        assert_abs_diff_eq!(matrix.try_inversed_closed_form().unwrap(), expected);
+        assert_abs_diff_eq!(matrix.try_inversed().unwrap(), expected);
+        assert_abs_diff_eq!(matrix.lu().inverse().unwrap(), expected);
+        let matrix = Matrix3::from_rows([[1e6, 0., 0.], [0., 1e-12, 0.], [0., 0., 1.]]);
+        assert!(matrix.try_inversed().is_some());
+    }
+
+    #[test]
+    fn it_keeps_inversed_total_for_singular_matrices() {
+        let inverse = Matrix4::<f64>::scale_matrix(1., 0., 1.).inversed();
+        assert!(inverse
+            .data
+            .iter()
+            .flatten()
+            .any(|value| !value.is_finite()));
+    }
+
+    #[test]
+    fn it_calculates_the_rank() {
+        assert_eq!(matrix5().rank(), 5);
+        assert_eq!(Matrix4::<f64>::new().rank(), 0);
+        let matrix = Matrix4::from_rows([
+            [1., 2., 3., 4.],
+            [2., 4., 6., 8.],
+            [0., 1., 0., 1.],
+            [1., 3., 3., 5.],
+        ]);
+        assert_eq!(matrix.rank(), 2);
+        let wide = Matrix::<3, 2>::from_rows([[1., 2., 3.], [2., 4., 7.]]);
+        assert_eq!(wide.rank(), 2);
+    }
+
+    #[test]
+    fn it_inverts_single_precision_matrices() {
+        let matrix = Matrix4::<f32>::rotation_y_matrix(0.7)
+            .scale(2., 1., 3.)
+            .translate(1., -2., 5.);
+        let inverse = matrix.try_inversed().unwrap();
+        // the default epsilon follows the precision
+        assert_abs_diff_eq!(matrix * inverse, Matrix4::identity());
+        assert_abs_diff_eq!(matrix.lu().det(), 6.);
+    }
+}
@@ -1,6 +1,15 @@
+mod animated;
 mod base;
+mod decomposition;
+mod lu;
 mod properties;
+mod quaternion;
+mod transform;
 mod transformations;
 
+pub use animated::AnimatedTransform;
 pub use base::{Matrix, Matrix2, Matrix3, Matrix4};
-
+pub use decomposition::Decomposition;
+pub use lu::Lu;
+pub use quaternion::Quaternion;
+pub use transform::Transform;
@@ -1,28 +1,31 @@
+use crate::float::Float;
+use crate::matrix::lu::is_singular;
 use crate::matrix::Matrix;
 
-impl Matrix<2, 2> {
-    pub fn det(&self) -> f64 {
+impl<T: Float> Matrix<2, 2, T> {
+    pub fn det(&self) -> T {
         self[(0, 0)] * self[(1, 1)] - self[(1, 0)] * self[(0, 1)]
     }
 }
 
-impl Matrix<3, 3> {
-    pub fn det(&self) -> f64 {
-        let mut res = 0.;
+impl<T: Float> Matrix<3, 3, T> {
+    pub fn det(&self) -> T {
+        let mut res = T::ZERO;
         for c in 0..3 {
             res += self[(0, c)] * self.cofactor(0, c)
         }
         res
     }
 
-    pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-        self.minor(row, col) * if (row + col) % 2 == 0 { 1. } else { -1. }
+    pub fn cofactor(&self, row: usize, col: usize) -> T {
+        self.minor(row, col) * if (row + col).is_multiple_of(2) { T::ONE } else { -T::ONE }
     }
 
+    // infinities and NaNs for singular matrices, see try_inversed
     pub fn inversed(&self) -> Self {
         let det = self.det();
 
-        let mut res = Matrix::<3, 3>::new();
+        let mut res = Matrix::<3, 3, T>::new();
         for row in 0..3 {
             for col in 0..3 {
                 res[(row, col)] = self.cofactor(col, row) / det
@@ -31,12 +34,12 @@ impl Matrix<3, 3> {
         res
     }
 
-    pub fn minor(&self, row: usize, col: usize) -> f64 {
+    pub fn minor(&self, row: usize, col: usize) -> T {
         self.submatrix(row, col).det()
     }
 
-    pub fn submatrix(&self, row: usize, col: usize) -> Matrix<2, 2> {
-        let mut res = Matrix::<2, 2>::default();
+    pub fn submatrix(&self, row: usize, col: usize) -> Matrix<2, 2, T> {
+        let mut res = Matrix::<2, 2, T>::default();
         let mut self_r = 0;
         for r in 0..2 {
             if self_r == row {
@@ -56,41 +59,93 @@ impl Matrix<3, 3> {
     }
 }
 
-impl Matrix<4, 4> {
-    pub fn det(&self) -> f64 {
-        let mut res = 0.;
+impl<T: Float> Matrix<4, 4, T> {
+    pub fn det(&self) -> T {
+        let mut res = T::ZERO;
         for c in 0..4 {
             res += self[(0, c)] * self.cofactor(0, c)
         }
         res
     }
 
-    pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-        self.minor(row, col) * if (row + col) % 2 == 0 { 1. } else { -1. }
+    pub fn cofactor(&self, row: usize, col: usize) -> T {
+        self.minor(row, col) * if (row + col).is_multiple_of(2) { T::ONE } else { -T::ONE }
     }
 
     pub fn is_invertible(&self) -> bool {
-        self.det() != 0.
+        self.try_inversed_closed_form().is_some()
     }
 
+    // infinities and NaNs for singular matrices, see try_inversed
     pub fn inversed(&self) -> Self {
-        let det = self.det();
+        let (adjugate, det) = self.adjugate();
+        adjugate.scaled(T::ONE / det)
+    }
 
-        let mut res = Matrix::<4, 4>::new();
-        for row in 0..4 {
-            for col in 0..4 {
-                res[(row, col)] = self.cofactor(col, row) / det
-            }
-        }
-        res
+    // try_inversed without the decomposition, about 2.5x faster and singular
+    // under the same rule
+    pub fn try_inversed_closed_form(&self) -> Option<Self> {
+        let (adjugate, det) = self.adjugate();
+        (!is_singular(det, self)).then(|| adjugate.scaled(T::ONE / det))
     }
 
-    pub fn minor(&self, row: usize, col: usize) -> f64 {
+    // the transposed cofactors and the determinant from the 2x2 determinants
+    // of the two upper rows and of the two lower rows, about three times
+    // fewer products than expanding every cofactor
+    fn adjugate(&self) -> (Self, T) {
+        let m = |row: usize, col: usize| self[(row, col)];
+        let s0 = m(0, 0) * m(1, 1) - m(1, 0) * m(0, 1);
+        let s1 = m(0, 0) * m(1, 2) - m(1, 0) * m(0, 2);
+        let s2 = m(0, 0) * m(1, 3) - m(1, 0) * m(0, 3);
+        let s3 = m(0, 1) * m(1, 2) - m(1, 1) * m(0, 2);
+        let s4 = m(0, 1) * m(1, 3) - m(1, 1) * m(0, 3);
+        let s5 = m(0, 2) * m(1, 3) - m(1, 2) * m(0, 3);
+        let c5 = m(2, 2) * m(3, 3) - m(3, 2) * m(2, 3);
+        let c4 = m(2, 1) * m(3, 3) - m(3, 1) * m(2, 3);
+        let c3 = m(2, 1) * m(3, 2) - m(3, 1) * m(2, 2);
+        let c2 = m(2, 0) * m(3, 3) - m(3, 0) * m(2, 3);
+        let c1 = m(2, 0) * m(3, 2) - m(3, 0) * m(2, 2);
+        let c0 = m(2, 0) * m(3, 1) - m(3, 0) * m(2, 1);
+        let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+        let adjugate = Matrix::from_rows([
+            [
+                m(1, 1) * c5 - m(1, 2) * c4 + m(1, 3) * c3,
+                -m(0, 1) * c5 + m(0, 2) * c4 - m(0, 3) * c3,
+                m(3, 1) * s5 - m(3, 2) * s4 + m(3, 3) * s3,
+                -m(2, 1) * s5 + m(2, 2) * s4 - m(2, 3) * s3,
+            ],
+            [
+                -m(1, 0) * c5 + m(1, 2) * c2 - m(1, 3) * c1,
+                m(0, 0) * c5 - m(0, 2) * c2 + m(0, 3) * c1,
+                -m(3, 0) * s5 + m(3, 2) * s2 - m(3, 3) * s1,
+                m(2, 0) * s5 - m(2, 2) * s2 + m(2, 3) * s1,
+            ],
+            [
+                m(1, 0) * c4 - m(1, 1) * c2 + m(1, 3) * c0,
+                -m(0, 0) * c4 + m(0, 1) * c2 - m(0, 3) * c0,
+                m(3, 0) * s4 - m(3, 1) * s2 + m(3, 3) * s0,
+                -m(2, 0) * s4 + m(2, 1) * s2 - m(2, 3) * s0,
+            ],
+            [
+                -m(1, 0) * c3 + m(1, 1) * c1 - m(1, 2) * c0,
+                m(0, 0) * c3 - m(0, 1) * c1 + m(0, 2) * c0,
+                -m(3, 0) * s3 + m(3, 1) * s1 - m(3, 2) * s0,
+                m(2, 0) * s3 - m(2, 1) * s1 + m(2, 2) * s0,
+            ],
+        ]);
+        (adjugate, det)
+    }
+
+    fn scaled(&self, factor: T) -> Self {
+        Matrix::from_rows(self.data.map(|row| row.map(|value| value * factor)))
+    }
+
+    pub fn minor(&self, row: usize, col: usize) -> T {
         self.submatrix(row, col).det()
     }
 
-    pub fn submatrix(&self, row: usize, col: usize) -> Matrix<3, 3> {
-        let mut res = Matrix::<3, 3>::default();
+    pub fn submatrix(&self, row: usize, col: usize) -> Matrix<3, 3, T> {
+        let mut res = Matrix::<3, 3, T>::default();
         let mut self_r = 0;
         for r in 0..3 {
             if self_r == row {
@@ -110,15 +165,15 @@ impl Matrix<4, 4> {
     }
 }
 
-// impl fmt::Display for Matrix<4, 4> {
+// impl fmt::Display for Matrix<4, 4, T> {
 //     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 //         write!(f, "({}, {})", self.x, self.y)
 //     }
 // }
 
-impl<const W: usize, const H: usize> Matrix<W, H> {
+impl<const W: usize, const H: usize, T: Float> Matrix<W, H, T> {
     pub fn transposed(&self) -> Self {
-        let mut res = Matrix::<W, H>::default();
+        let mut res = Matrix::<W, H, T>::default();
         for row in 0..H {
             for col in 0..W {
                 res[(row, col)] = self[(col, row)];
@@ -126,66 +181,8 @@ impl<const W: usize, const H: usize> Matrix<W, H> {
         }
         res
     }
-
-    // pub fn det(&self) -> f64 {
-    //     let mut res = 0.;
-    //     for c in 0..W {
-    //         res += self[(0, c)] * self.cofactor(0, c)
-    //     }
-    //     res
-    // }
-
-    // pub fn submatrix(&self, row: usize, col: usize) -> Matrix<W, H> {
-    //     let mut res = Matrix::zeroed(self.height - 1, self.width - 1);
-    //     let mut self_r = 0;
-    //     for r in 0..res.height {
-    //         if self_r == row {
-    //             self_r += 1
-    //         }
-    //         let mut self_c = 0;
-    //         for c in 0..res.width {
-    //             if self_c == col {
-    //                 self_c += 1
-    //             }
-    //             res[(r, c)] = self[(self_r, self_c)];
-    //             self_c += 1
-    //         }
-    //         self_r += 1
-    //     }
-    //     res
-    // }
-    // pub fn minor(&self, row: usize, col: usize) -> f64 {
-    //     assert!(self.is_square());
-    //     self.submatrix(row, col).det()
-    // }
-    //
-    // pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-    //     assert!(self.is_square());
-    //     self.minor(row, col) * if (row + col) % 2 == 0 { 1. } else { -1. }
-    // }
-
-    // pub fn is_invertible(&self) -> bool {
-    //     self.is_square() && self.det() != 0.
-    // }
-
-    // pub fn inversed(&self) -> Self {
-    //     assert!(self.is_square());
-    //     let det = self.det();
-
-    //     let mut res = Matrix::zeroed(self.height, self.width);
-    //     for row in 0..res.height {
-    //         for col in 0..res.width {
-    //             res[(row, col)] = self.cofactor(col, row) / det
-    //         }
-    //     }
-    //     res
-    // }
-
-    //     pub fn is_square(&self) -> bool {
-    //         self.width == self.height
-    //     }
-    // }
 }
+
 #[cfg(test)]
 mod tests {
     use crate::matrix::{Matrix2, Matrix3, Matrix4};
@@ -209,7 +206,7 @@ mod tests {
 
     #[test]
     fn it_transpose_the_identity_matrix() {
-        let identity = Matrix4::identity();
+        let identity = Matrix4::<f64>::identity();
         assert_abs_diff_eq!(identity.transposed(), identity);
     }
 
@@ -298,7 +295,7 @@ mod tests {
             [0., 0., 0., 0.],
         ]);
         assert_abs_diff_eq!(matrix.det(), 0.);
-        assert_eq!(matrix.is_invertible(), false);
+        assert!(!matrix.is_invertible());
     }
 
     #[test]
@@ -339,7 +336,7 @@ mod tests {
         ]);
 
         let inversed = matrix2.inversed();
-        let expected = matrix1.clone();
+        let expected = matrix1;
 
         assert_abs_diff_eq!(matrix1 * matrix2 * inversed, expected);
     }
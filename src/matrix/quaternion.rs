@@ -0,0 +1,366 @@
+use crate::float::Float;
+use crate::matrix::Matrix4;
+use crate::tuple::Vector3;
+use approx::AbsDiffEq;
+use std::ops::{Add, Mul};
+
+// unit quaternions represent rotations, w is the scalar part
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion<T = f64> {
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Float> Quaternion<T> {
+    pub fn new(w: T, x: T, y: T, z: T) -> Self {
+        Self { w, x, y, z }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(T::ONE, T::ZERO, T::ZERO, T::ZERO)
+    }
+
+    // a rotation of `angle` radians around `axis`, counterclockwise when the
+    // axis points toward the viewer like the rotation matrices
+    pub fn from_axis_angle(axis: Vector3<T>, angle: T) -> Self {
+        let half = angle / T::from_f64(2.);
+        let axis = axis.normalized() * half.sin();
+        Self::new(half.cos(), axis.x, axis.y, axis.z)
+    }
+
+    // the angle is in 0..=PI, the axis is x for the identity
+    pub fn to_axis_angle(&self) -> (Vector3<T>, T) {
+        let q = self.normalized();
+        // q and -q are the same rotation, pick the one with the smaller angle
+        let q = if q.w < T::ZERO { q * -T::ONE } else { q };
+        let sin_half = (q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
+        if sin_half < T::from_f64(1e-12) {
+            return (Vector3::vector(T::ONE, T::ZERO, T::ZERO), T::ZERO);
+        }
+        let axis = Vector3::vector(q.x / sin_half, q.y / sin_half, q.z / sin_half);
+        (axis, T::from_f64(2.) * sin_half.atan2(q.w))
+    }
+
+    // rotate around x, then y, then z, like
+    // `Matrix4::rotation_x_matrix(x).rotate_y(y).rotate_z(z)`
+    pub fn from_euler(x: T, y: T, z: T) -> Self {
+        let (zero, one) = (T::ZERO, T::ONE);
+        Self::from_axis_angle(Vector3::vector(zero, zero, one), z)
+            * Self::from_axis_angle(Vector3::vector(zero, one, zero), y)
+            * Self::from_axis_angle(Vector3::vector(one, zero, zero), x)
+    }
+
+    // the (x, y, z) angles of `from_euler`, y is in -PI/2..=PI/2 and x takes
+    // the whole rotation around the locked axis when y is at either end
+    pub fn to_euler(&self) -> (T, T, T) {
+        let m = self.to_matrix();
+        let sin_y = (-m[(2, 0)]).clamp(-T::ONE, T::ONE);
+        // 1e-12 is below the resolution of f32
+        if sin_y.abs() > T::ONE - T::from_f64(1e-12).max(T::EPSILON) {
+            return ((-m[(1, 2)]).atan2(m[(1, 1)]), sin_y.asin(), T::ZERO);
+        }
+        (
+            m[(2, 1)].atan2(m[(2, 2)]),
+            sin_y.asin(),
+            m[(1, 0)].atan2(m[(0, 0)]),
+        )
+    }
+
+    // the upper 3x3 of `matrix` has to be a pure rotation
+    pub fn from_rotation_matrix(matrix: &Matrix4<T>) -> Self {
+        let m = |row: usize, col: usize| matrix[(row, col)];
+        let (one, two, four) = (T::ONE, T::from_f64(2.), T::from_f64(4.));
+        let trace = m(0, 0) + m(1, 1) + m(2, 2);
+        // Shepperd's method, pivot on the largest component to stay stable
+        let quaternion = if trace > T::ZERO {
+            let s = (trace + one).sqrt() * two;
+            Self::new(
+                s / four,
+                (m(2, 1) - m(1, 2)) / s,
+                (m(0, 2) - m(2, 0)) / s,
+                (m(1, 0) - m(0, 1)) / s,
+            )
+        } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+            let s = (one + m(0, 0) - m(1, 1) - m(2, 2)).sqrt() * two;
+            Self::new(
+                (m(2, 1) - m(1, 2)) / s,
+                s / four,
+                (m(0, 1) + m(1, 0)) / s,
+                (m(0, 2) + m(2, 0)) / s,
+            )
+        } else if m(1, 1) > m(2, 2) {
+            let s = (one + m(1, 1) - m(0, 0) - m(2, 2)).sqrt() * two;
+            Self::new(
+                (m(0, 2) - m(2, 0)) / s,
+                (m(0, 1) + m(1, 0)) / s,
+                s / four,
+                (m(1, 2) + m(2, 1)) / s,
+            )
+        } else {
+            let s = (one + m(2, 2) - m(0, 0) - m(1, 1)).sqrt() * two;
+            Self::new(
+                (m(1, 0) - m(0, 1)) / s,
+                (m(0, 2) + m(2, 0)) / s,
+                (m(1, 2) + m(2, 1)) / s,
+                s / four,
+            )
+        };
+        quaternion.normalized()
+    }
+
+    pub fn dot(&self, other: Self) -> T {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn magnitude(&self) -> T {
+        self.dot(*self).sqrt()
+    }
+
+    pub fn normalized(&self) -> Self {
+        *self * (T::ONE / self.magnitude())
+    }
+
+    // the inverse rotation for unit quaternions
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn rotate(&self, vector: Vector3<T>) -> Vector3<T> {
+        let q = self.normalized();
+        let u = Vector3::vector(q.x, q.y, q.z);
+        let t = u.cross(vector) * T::from_f64(2.);
+        vector + t * q.w + u.cross(t)
+    }
+
+    pub fn to_matrix(self) -> Matrix4<T> {
+        let Self { w, x, y, z } = self.normalized();
+        let (zero, one, two) = (T::ZERO, T::ONE, T::from_f64(2.));
+        Matrix4::from_rows([
+            [
+                one - two * (y * y + z * z),
+                two * (x * y - w * z),
+                two * (x * z + w * y),
+                zero,
+            ],
+            [
+                two * (x * y + w * z),
+                one - two * (x * x + z * z),
+                two * (y * z - w * x),
+                zero,
+            ],
+            [
+                two * (x * z - w * y),
+                two * (y * z + w * x),
+                one - two * (x * x + y * y),
+                zero,
+            ],
+            [zero, zero, zero, one],
+        ])
+    }
+
+    // spherical linear interpolation along the shortest arc
+    pub fn slerp(&self, other: Self, t: T) -> Self {
+        let mut other = other;
+        let mut cos_theta = self.dot(other);
+        // q and -q are the same rotation, take the closest one
+        if cos_theta < T::ZERO {
+            other = other * -T::ONE;
+            cos_theta = -cos_theta;
+        }
+        if cos_theta > T::from_f64(0.9995) {
+            // nearly parallel, a normalized lerp is accurate and stable
+            return (*self * (T::ONE - t) + other * t).normalized();
+        }
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        let a = ((T::ONE - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        *self * a + other * b
+    }
+}
+
+impl<T: Float> Add for Quaternion<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.w + other.w,
+            self.x + other.x,
+            self.y + other.y,
+            self.z + other.z,
+        )
+    }
+}
+
+// the Hamilton product, `self * other` rotates by `other` and then by `self`
+impl<T: Float> Mul for Quaternion<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        )
+    }
+}
+
+impl<T: Float> Mul<T> for Quaternion<T> {
+    type Output = Self;
+
+    fn mul(self, other: T) -> Self {
+        Self::new(
+            self.w * other,
+            self.x * other,
+            self.y * other,
+            self.z * other,
+        )
+    }
+}
+
+impl<T: Float> AbsDiffEq for Quaternion<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        T::abs_diff_eq(&self.w, &other.w, epsilon)
+            && T::abs_diff_eq(&self.x, &other.x, epsilon)
+            && T::abs_diff_eq(&self.y, &other.y, epsilon)
+            && T::abs_diff_eq(&self.z, &other.z, epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::Quaternion;
+    use crate::matrix::Matrix4;
+    use crate::tuple::{Point3, Vector3};
+
+    #[test]
+    fn it_converts_rotation_matrices_back_and_forth() {
+        for matrix in [
+            Matrix4::identity(),
+            Matrix4::rotation_x_matrix(PI / 3.),
+            Matrix4::rotation_y_matrix(-2.5),
+            Matrix4::rotation_z_matrix(PI),
+            Matrix4::rotation_x_matrix(0.3).rotate_y(1.2).rotate_z(-2.9),
+        ] {
+            let quaternion = Quaternion::from_rotation_matrix(&matrix);
+            assert_abs_diff_eq!(quaternion.magnitude(), 1., epsilon = 1e-12);
+            assert_abs_diff_eq!(quaternion.to_matrix(), matrix, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn it_interpolates_rotations_with_a_constant_angular_speed() {
+        let start = Quaternion::identity();
+        let end = Quaternion::from_rotation_matrix(&Matrix4::rotation_z_matrix(PI / 2.));
+        let half = start.slerp(end, 0.5);
+        assert_abs_diff_eq!(
+            half.to_matrix(),
+            Matrix4::rotation_z_matrix(PI / 4.),
+            epsilon = 1e-12
+        );
+        let quarter = start.slerp(end, 0.25);
+        assert_abs_diff_eq!(
+            quarter.to_matrix(),
+            Matrix4::rotation_z_matrix(PI / 8.),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_interpolates_along_the_shortest_arc() {
+        let start = Quaternion::from_rotation_matrix(&Matrix4::rotation_y_matrix(0.1));
+        let end = Quaternion::from_rotation_matrix(&Matrix4::rotation_y_matrix(0.3)) * -1.;
+        assert_abs_diff_eq!(
+            start.slerp(end, 0.5).to_matrix(),
+            Matrix4::rotation_y_matrix(0.2),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_rotates_around_an_arbitrary_axis() {
+        let axis = Vector3::vector(1., 1., 1.);
+        // a third of a turn around the diagonal cycles the axes
+        let q = Quaternion::from_axis_angle(axis, 2. * PI / 3.);
+        assert_abs_diff_eq!(
+            q.rotate(Vector3::vector(1., 0., 0.)),
+            Vector3::vector(0., 1., 0.),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            q.to_matrix() * Point3::point(0., 0., 2.),
+            Point3::point(2., 0., 0.),
+            epsilon = 1e-12
+        );
+        let q = Quaternion::from_axis_angle(Vector3::vector(0., 0., 3.), 0.7);
+        assert_abs_diff_eq!(
+            q.to_matrix(),
+            Matrix4::rotation_z_matrix(0.7),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_converts_back_to_an_axis_and_an_angle() {
+        let axis = Vector3::vector(2., -1., 0.5).normalized();
+        let (found_axis, angle) = Quaternion::from_axis_angle(axis, 1.3).to_axis_angle();
+        assert_abs_diff_eq!(found_axis, axis, epsilon = 1e-12);
+        assert_abs_diff_eq!(angle, 1.3, epsilon = 1e-12);
+        // the opposite axis for rotations of more than half a turn
+        let (found_axis, angle) = Quaternion::from_axis_angle(axis, 1.5 * PI).to_axis_angle();
+        assert_abs_diff_eq!(found_axis, -axis, epsilon = 1e-12);
+        assert_abs_diff_eq!(angle, 0.5 * PI, epsilon = 1e-12);
+        assert_eq!(Quaternion::<f64>::identity().to_axis_angle().1, 0.);
+    }
+
+    #[test]
+    fn it_composes_rotations() {
+        let x = Quaternion::from_axis_angle(Vector3::vector(1., 0., 0.), 0.4);
+        let y = Quaternion::from_axis_angle(Vector3::vector(0., 1., 0.), -1.1);
+        assert_abs_diff_eq!(
+            (y * x).to_matrix(),
+            Matrix4::rotation_x_matrix(0.4).rotate_y(-1.1),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(x * x.conjugate(), Quaternion::identity(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_converts_euler_angles_back_and_forth() {
+        let (x, y, z) = (0.3, -0.8, 2.2);
+        let q = Quaternion::from_euler(x, y, z);
+        assert_abs_diff_eq!(
+            q.to_matrix(),
+            Matrix4::rotation_x_matrix(x).rotate_y(y).rotate_z(z),
+            epsilon = 1e-12
+        );
+        let (ex, ey, ez) = q.to_euler();
+        assert_abs_diff_eq!(ex, x, epsilon = 1e-12);
+        assert_abs_diff_eq!(ey, y, epsilon = 1e-12);
+        assert_abs_diff_eq!(ez, z, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_keeps_the_rotation_in_gimbal_lock() {
+        let q = Quaternion::from_euler(0.5, PI / 2., 0.2);
+        let (x, y, z) = q.to_euler();
+        assert_abs_diff_eq!(y, PI / 2., epsilon = 1e-6);
+        assert_abs_diff_eq!(z, 0.);
+        assert_abs_diff_eq!(
+            Quaternion::from_euler(x, y, z).to_matrix(),
+            q.to_matrix(),
+            epsilon = 1e-6
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::float::Float;
+use crate::matrix::Matrix4;
+use crate::tuple::{Normal3, Point3, Vector3};
+use approx::AbsDiffEq;
+use std::ops::Mul;
+
+// a matrix with its inverse and inverse transpose, computed once when the
+// transform is set instead of for every ray
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform<T = f64> {
+    matrix: Matrix4<T>,
+    // brings world space points and rays into object space
+    inverse: Matrix4<T>,
+    // brings object space normals back into world space
+    inverse_transpose: Matrix4<T>,
+}
+
+impl<T: Float> Transform<T> {
+    // panics for singular matrices, like a sphere flattened by a zero scale,
+    // see try_new
+    pub fn new(matrix: Matrix4<T>) -> Self {
+        Self::try_new(matrix).expect("the transform matrix is not invertible")
+    }
+
+    pub fn try_new(matrix: Matrix4<T>) -> Option<Self> {
+        Some(Self::with_inverse(
+            matrix,
+            matrix.try_inversed_closed_form()?,
+        ))
+    }
+
+    // for callers that get the inverse cheaper than by inverting `matrix`
+    pub(crate) fn with_inverse(matrix: Matrix4<T>, inverse: Matrix4<T>) -> Self {
+        Self {
+            matrix,
+            inverse,
+            inverse_transpose: inverse.transposed(),
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(Matrix4::identity())
+    }
+
+    pub fn matrix(&self) -> Matrix4<T> {
+        self.matrix
+    }
+
+    pub fn inverse(&self) -> Matrix4<T> {
+        self.inverse
+    }
+
+    pub fn inverse_transpose(&self) -> Matrix4<T> {
+        self.inverse_transpose
+    }
+}
+
+impl<T: Float> Default for Transform<T> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<T: Float> From<Matrix4<T>> for Transform<T> {
+    fn from(matrix: Matrix4<T>) -> Self {
+        Self::new(matrix)
+    }
+}
+
+impl<T: Float> Mul<Point3<T>> for Transform<T> {
+    type Output = Point3<T>;
+
+    fn mul(self, other: Point3<T>) -> Point3<T> {
+        self.matrix * other
+    }
+}
+
+impl<T: Float> Mul<Vector3<T>> for Transform<T> {
+    type Output = Vector3<T>;
+
+    fn mul(self, other: Vector3<T>) -> Vector3<T> {
+        self.matrix * other
+    }
+}
+
+// not normalized, a scale changes the length of the normal
+impl<T: Float> Mul<Normal3<T>> for Transform<T> {
+    type Output = Normal3<T>;
+
+    fn mul(self, other: Normal3<T>) -> Normal3<T> {
+        Normal3::from(self.inverse_transpose * other.to_vector())
+    }
+}
+
+impl<T: Float> AbsDiffEq for Transform<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        Matrix4::default_epsilon()
+    }
+
+    // the cached matrices follow from the transform itself
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        Matrix4::abs_diff_eq(&self.matrix, &other.matrix, epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::matrix::Matrix4;
+    use crate::tuple::{Normal3, Point3, Vector3};
+
+    #[test]
+    fn it_caches_the_inverse_and_its_transpose() {
+        let matrix = Matrix4::rotation_y_matrix(0.7)
+            .scale(2., 1., 3.)
+            .translate(1., -2., 5.);
+        let transform = Transform::new(matrix);
+        assert_eq!(transform.matrix(), matrix);
+        assert_abs_diff_eq!(transform.inverse(), matrix.inversed());
+        assert_abs_diff_eq!(
+            transform.inverse_transpose(),
+            matrix.inversed().transposed()
+        );
+        let point = Point3::point(1., 2., 3.);
+        assert_abs_diff_eq!(
+            transform.inverse() * (transform.matrix() * point),
+            point,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_refuses_singular_matrices() {
+        assert_eq!(
+            Transform::try_new(Matrix4::<f64>::scale_matrix(1., 0., 1.)),
+            None
+        );
+        assert!(Transform::try_new(Matrix4::scale_matrix(1., 1e-12, 1.)).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "the transform matrix is not invertible")]
+    fn it_panics_on_singular_matrices_without_try() {
+        Transform::new(Matrix4::<f64>::scale_matrix(1., 0., 1.));
+    }
+
+    #[test]
+    fn it_defaults_to_the_identity() {
+        let transform = Transform::<f64>::default();
+        assert_eq!(transform.matrix(), Matrix4::identity());
+        assert_eq!(transform.inverse(), Matrix4::identity());
+        assert_eq!(transform, Matrix4::identity().into());
+    }
+
+    #[test]
+    fn it_transforms_points_vectors_and_normals() {
+        let transform = Transform::new(Matrix4::scale_matrix(1., 2., 1.).translate(3., 0., 0.));
+        assert_abs_diff_eq!(
+            transform * Point3::point(1., 1., 1.),
+            Point3::point(4., 2., 1.)
+        );
+        assert_abs_diff_eq!(
+            transform * Vector3::vector(1., 1., 1.),
+            Vector3::vector(1., 2., 1.)
+        );
+        // the normal of the plane x + y = 0 stays perpendicular to it
+        let normal = transform * Normal3::normal(1., 1., 0.);
+        assert_abs_diff_eq!(normal, Normal3::normal(1., 0.5, 0.));
+        let along_the_plane = transform * Vector3::vector(1., -1., 0.);
+        assert_abs_diff_eq!(normal.dot(along_the_plane), 0.);
+    }
+}
@@ -1,7 +1,9 @@
-use crate::matrix::Matrix4;
+use crate::float::Float;
+use crate::matrix::{Matrix4, Quaternion};
+use crate::tuple::{Point3, Vector3};
 
-impl Matrix4 {
-    pub fn translation_matrix(x: f64, y: f64, z: f64) -> Self {
+impl<T: Float> Matrix4<T> {
+    pub fn translation_matrix(x: T, y: T, z: T) -> Self {
         let mut matrix = Matrix4::identity();
         matrix[(0, 3)] = x;
         matrix[(1, 3)] = y;
@@ -10,7 +12,7 @@ impl Matrix4 {
     }
 
   
-    pub fn scale_matrix(x: f64, y: f64, z: f64) -> Self {
+    pub fn scale_matrix(x: T, y: T, z: T) -> Self {
         let mut matrix = Matrix4::identity();
         matrix[(0, 0)] = x;
         matrix[(1, 1)] = y;
@@ -18,61 +20,84 @@ impl Matrix4 {
         matrix
     }
 
-    pub fn rotation_x_matrix(a: f64) -> Self {
+    pub fn rotation_x_matrix(a: T) -> Self {
         Matrix4::from_rows([
-            [1., 0., 0., 0.],
-            [0., a.cos(), -a.sin(), 0.],
-            [0., a.sin(), a.cos(), 0.],
-            [0., 0., 0., 1.],
+            [T::ONE, T::ZERO, T::ZERO, T::ZERO],
+            [T::ZERO, a.cos(), -a.sin(), T::ZERO],
+            [T::ZERO, a.sin(), a.cos(), T::ZERO],
+            [T::ZERO, T::ZERO, T::ZERO, T::ONE],
         ])
     }
 
-    pub fn rotation_y_matrix(a: f64) -> Self {
+    pub fn rotation_y_matrix(a: T) -> Self {
         Matrix4::from_rows([
-            [a.cos(), 0., a.sin(), 0.],
-            [0., 1., 0., 0.],
-            [-a.sin(), 0., a.cos(), 0.],
-            [0., 0., 0., 1.],
+            [a.cos(), T::ZERO, a.sin(), T::ZERO],
+            [T::ZERO, T::ONE, T::ZERO, T::ZERO],
+            [-a.sin(), T::ZERO, a.cos(), T::ZERO],
+            [T::ZERO, T::ZERO, T::ZERO, T::ONE],
         ])
     }
 
-    pub fn rotation_z_matrix(a: f64) -> Self {
+    pub fn rotation_z_matrix(a: T) -> Self {
         Matrix4::from_rows([
-            [a.cos(), -a.sin(), 0., 0.],
-            [a.sin(), a.cos(), 0., 0.],
-            [0., 0., 1., 0.],
-            [0., 0., 0., 1.],
+            [a.cos(), -a.sin(), T::ZERO, T::ZERO],
+            [a.sin(), a.cos(), T::ZERO, T::ZERO],
+            [T::ZERO, T::ZERO, T::ONE, T::ZERO],
+            [T::ZERO, T::ZERO, T::ZERO, T::ONE],
         ])
     }
 
-    pub fn shear_matrix(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Self {
+    pub fn shear_matrix(xy: T, xz: T, yx: T, yz: T, zx: T, zy: T) -> Self {
         Matrix4::from_rows([
-            [1., xy, xz, 0.],
-            [yx, 1., yz, 0.],
-            [zx, zy, 1., 0.],
-            [0., 0., 0., 1.],
+            [T::ONE, xy, xz, T::ZERO],
+            [yx, T::ONE, yz, T::ZERO],
+            [zx, zy, T::ONE, T::ZERO],
+            [T::ZERO, T::ZERO, T::ZERO, T::ONE],
         ])
     }
 
-    pub fn translate(&self, x: f64, y: f64, z: f64) -> Self {
+    pub fn translate(&self, x: T, y: T, z: T) -> Self {
          Matrix4::translation_matrix(x,y,z) * self
     }
 
-    pub fn scale(&self, x: f64, y: f64, z: f64) -> Self {
+    pub fn scale(&self, x: T, y: T, z: T) -> Self {
         Matrix4::scale_matrix(x,y,z) * self
     }
 
-    pub fn rotate_x(&self, a: f64) -> Self {
+    pub fn rotate_x(&self, a: T) -> Self {
         Matrix4::rotation_x_matrix(a) * self
     }
 
-    pub fn rotate_y(&self, a: f64) -> Self {
+    pub fn rotate_y(&self, a: T) -> Self {
         Matrix4::rotation_y_matrix(a) * self
     }
 
-    pub fn rotate_z(&self, a: f64) -> Self {
+    pub fn rotate_z(&self, a: T) -> Self {
         Matrix4::rotation_z_matrix(a) * self
     }
+
+    // orient the world relative to an eye at `from` looking at `to`
+    pub fn view_transform(from: Point3<T>, to: Point3<T>, up: Vector3<T>) -> Self {
+        let forward = (to - from).normalized();
+        let left = forward.cross(up.normalized());
+        let true_up = left.cross(forward);
+        let orientation = Matrix4::from_rows([
+            [left.x, left.y, left.z, T::ZERO],
+            [true_up.x, true_up.y, true_up.z, T::ZERO],
+            [-forward.x, -forward.y, -forward.z, T::ZERO],
+            [T::ZERO, T::ZERO, T::ZERO, T::ONE],
+        ]);
+        orientation * Matrix4::translation_matrix(-from.x, -from.y, -from.z)
+    }
+
+    // counterclockwise around `axis` when it points toward the viewer
+    pub fn rotation_matrix(axis: Vector3<T>, a: T) -> Self {
+        Quaternion::from_axis_angle(axis, a).to_matrix()
+    }
+
+    pub fn rotate(&self, axis: Vector3<T>, a: T) -> Self {
+        Matrix4::rotation_matrix(axis, a) * self
+    }
 }
 
 #[cfg(test)]
@@ -249,4 +274,66 @@ mod tests {
         let point2 = matrix * point1;
         assert_abs_diff_eq!(point2, Point3::point(15., 0., 7.));
     }
+
+    #[test]
+    fn it_has_an_identity_view_transform_for_the_default_orientation() {
+        let from = Point3::point(0., 0., 0.);
+        let to = Point3::point(0., 0., -1.);
+        let up = Vector3::vector(0., 1., 0.);
+        assert_abs_diff_eq!(Matrix4::view_transform(from, to, up), Matrix4::identity());
+    }
+
+    #[test]
+    fn it_mirrors_the_world_when_looking_in_the_positive_z_direction() {
+        let from = Point3::point(0., 0., 0.);
+        let to = Point3::point(0., 0., 1.);
+        let up = Vector3::vector(0., 1., 0.);
+        assert_abs_diff_eq!(
+            Matrix4::view_transform(from, to, up),
+            Matrix4::scale_matrix(-1., 1., -1.)
+        );
+    }
+
+    #[test]
+    fn it_moves_the_world_with_the_view_transform() {
+        let from = Point3::point(0., 0., 8.);
+        let to = Point3::point(0., 0., 0.);
+        let up = Vector3::vector(0., 1., 0.);
+        assert_abs_diff_eq!(
+            Matrix4::view_transform(from, to, up),
+            Matrix4::translation_matrix(0., 0., -8.)
+        );
+    }
+
+    #[test]
+    fn it_computes_an_arbitrary_view_transform() {
+        let from = Point3::point(1., 3., 2.);
+        let to = Point3::point(4., -2., 8.);
+        let up = Vector3::vector(1., 1., 0.);
+        let expected = Matrix4::from_rows([
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.],
+            [0., 0., 0., 1.],
+        ]);
+        assert_abs_diff_eq!(
+            Matrix4::view_transform(from, to, up),
+            expected,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn it_rotates_around_an_arbitrary_axis() {
+        let matrix = Matrix4::rotation_matrix(Vector3::vector(0., 2., 0.), PI / 3.);
+        assert_abs_diff_eq!(matrix, Matrix4::rotation_y_matrix(PI / 3.), epsilon = 1e-12);
+        let chained = Matrix4::identity().rotate(Vector3::vector(1., 0., 0.), PI / 2.);
+        assert_abs_diff_eq!(
+            chained * Point3::point(0., 1., 0.),
+            Point3::point(0., 0., 1.),
+            epsilon = 1e-12
+        );
+        let single = Matrix4::<f32>::rotation_matrix(Vector3::vector(0., 2., 0.), PI as f32 / 3.);
+        assert_abs_diff_eq!(single, Matrix4::rotation_y_matrix(PI as f32 / 3.));
+    }
 }
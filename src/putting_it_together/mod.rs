@@ -3,4 +3,6 @@ pub mod putting_it_together2;
 pub mod putting_it_together3;
 pub mod putting_it_together4;
 pub mod putting_it_together5;
+pub mod putting_it_together6;
+pub mod putting_it_together7;
 mod shared;
\ No newline at end of file
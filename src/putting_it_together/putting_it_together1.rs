@@ -1,14 +1,14 @@
-use crate::tuple::Tuple;
+use crate::tuple::{Point3, Vector3};
 use crate::putting_it_together::shared::{Environment, Projectile, tick};
 
 pub fn run() {
     let mut p = Projectile {
-        position: Tuple::point(0., 1., 0.),
-        velocity: Tuple::point(1., 1., 0.).normalized(),
+        position: Point3::point(0., 1., 0.),
+        velocity: Vector3::vector(1., 1., 0.).normalized(),
     };
     let e = Environment {
-        gravity: Tuple::point(0., -0.1, 0.),
-        wind: Tuple::point(-0.01, 0., 0.).normalized(),
+        gravity: Vector3::vector(0., -0.1, 0.),
+        wind: Vector3::vector(-0.01, 0., 0.).normalized(),
     };
 
     while p.position.y > 0. {
@@ -1,4 +1,4 @@
-use crate::tuple::Tuple;
+use crate::tuple::{Point3, Vector3};
 use crate::putting_it_together::shared::{Environment, Projectile, tick};
 use crate::canvas::Canvas;
 use crate::color::Color;
@@ -6,19 +6,23 @@ use crate::color::Color;
 
 pub fn run() {
     let mut p = Projectile {
-        position: Tuple::point(0., 1., 0.),
-        velocity: Tuple::point(1., 1.8, 0.).normalized() * 11.25,
+        position: Point3::point(0., 1., 0.),
+        velocity: Vector3::vector(1., 1.8, 0.).normalized() * 11.25,
     };
     let e = Environment {
-        gravity: Tuple::point(0., -0.1, 0.),
-        wind: Tuple::point(-0.01, 0., 0.).normalized(),
+        gravity: Vector3::vector(0., -0.1, 0.),
+        wind: Vector3::vector(-0.01, 0., 0.).normalized(),
     };
 
     let mut c = Canvas::new(900, 550, Color::black());
 
     while p.position.y > 0. {
+        let from = (p.position.x, p.position.y);
         p = tick(&e, p);
-        c.write_pixel(p.position.x as usize,  549 - p.position.y as usize, Color::white())
+        c.draw_line(from, (p.position.x, p.position.y), Color::white());
     }
+    // the projectile flies with y pointing up
+    let mut c = c.flip_vertical();
+    c.draw_text(10, 10, "projectile", 2, Color::new(1., 0.8, 0.));
     println!("{}", c.to_ppm());
 }
\ No newline at end of file
@@ -3,7 +3,7 @@ use std::f64::consts::PI;
 use crate::canvas::Canvas;
 use crate::color::Color;
 use crate::matrix::Matrix4;
-use crate::tuple::Tuple;
+use crate::tuple::Point3;
 
 pub fn run() {
     let mut c = Canvas::new(512, 512, Color::black());
@@ -12,10 +12,12 @@ pub fn run() {
         let matrix = Matrix4::identity()
             .rotate_z(PI / 6. * a as f64)
             .translate(255., 255., 0.);
-        let point = Tuple::point(200., 0., 0.);
+        let point = Point3::point(200., 0., 0.);
         let hour = matrix * point;
-        c.write_pixel(hour.x as usize,  511 - hour.y as usize, Color::white())
+        c.fill_circle((hour.x, hour.y), 4., Color::white());
     }
+    let mut c = c.flip_vertical();
+    c.draw_text(241, 249, "XII", 2, Color::new(1., 0.8, 0.));
 
     println!("{}", c.to_ppm());
 }
@@ -19,9 +19,9 @@ pub fn run() {
     let shape = Sphere::new(Point3::point(0., 0., 0.), 1.);
 
     for y in 0..CANVAS_SIZE {
-        let world_y = (-half + pixel_size * (y as f64)) as f64;
+        let world_y = -half + pixel_size * (y as f64);
         for x in 0..CANVAS_SIZE {
-            let world_x = (-half + pixel_size * (x as f64)) as f64;
+            let world_x = -half + pixel_size * (x as f64);
             let position = Point3::point(world_x, world_y, 10.);
             let r = Ray::new(ray_origin, (position - ray_origin).normalized());
             let xs = shape.intersections(&r);
@@ -1,10 +1,17 @@
+use std::env;
 use std::f64::consts::PI;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::canvas::Canvas;
 use crate::color::Color;
 use crate::geo::{Ray, Sphere};
 use crate::matrix::Matrix4;
+use crate::render::{
+    CancellationToken, Checkpoint, Filter, Progress, Renderer, Sample, SamplePattern, Sampler,
+    Snapshot,
+};
 use crate::scene::PointLight;
 use crate::tuple::{Point3, Vector3};
 
@@ -23,26 +30,55 @@ pub fn run() {
     let ligth_color = Color::new(1., 1., 1.);
     let light = PointLight::new(light_position, ligth_color);
 
-    for y in 0..CANVAS_SIZE {
-        let world_y = (-half + pixel_size * (y as f64)) as f64;
-        for x in 0..CANVAS_SIZE {
-            let world_x = (-half + pixel_size * (x as f64)) as f64;
-            let position = Point3::point(world_x, world_y, 10.);
-            let r = Ray::new(ray_origin, (position - ray_origin).normalized());
-            let xs = shape.intersections(&r);
-            if xs.count() > 0 {
-                let hit = &xs[0];
-                let position = r.position(hit.t);
-                let normal = hit.intersectable.normal_at(position);
-                let eye = -r.direction;
-                let color = hit
-                    .intersectable
-                    .get_material()
-                    .lighting(light, position, eye, normal);
-                c.write_pixel(x, CANVAS_SIZE - y, color)
-            }
+    let shader = |sample: &Sample| {
+        // the canvas y axis points down while the world y axis points up
+        let world_y = half - pixel_size * sample.y;
+        let world_x = -half + pixel_size * sample.x;
+        let position = Point3::point(world_x, world_y, 10.);
+        let r = Ray::new(ray_origin, (position - ray_origin).normalized());
+        let xs = shape.intersections(&r);
+        if xs.count() > 0 {
+            let hit = &xs[0];
+            let position = r.position(hit.t);
+            let normal = hit.intersectable.normal_at(position);
+            let eye = -r.direction;
+            hit.intersectable
+                .get_material()
+                .lighting(light, position, eye, normal)
+        } else {
+            Color::black()
         }
+    };
+
+    let mut renderer = Renderer::new();
+    renderer.sampler = Sampler::new(4, SamplePattern::Jittered, Filter::mitchell());
+    renderer.snapshot = Some(Snapshot::new(
+        PathBuf::from("out/progress.png"),
+        Duration::from_secs(1),
+    ));
+    let checkpoint_path = PathBuf::from("out/test.ckpt");
+    renderer.checkpoint = Some(Snapshot::new(
+        checkpoint_path.clone(),
+        Duration::from_secs(10),
+    ));
+    let report = |progress: &Progress| {
+        print!(
+            "\r{:.0}% ({}/{} tiles)",
+            progress.percent(),
+            progress.tiles_done,
+            progress.tiles_total
+        );
+        std::io::stdout().flush().ok();
+    };
+    let token = CancellationToken::new();
+    if env::args().any(|arg| arg == "--resume") {
+        let checkpoint = Checkpoint::load(&checkpoint_path).expect("Couldn’t load the checkpoint");
+        renderer.resume(&mut c, &checkpoint, shader, report, &token)
+    } else {
+        renderer.render(&mut c, shader, report, &token)
     }
+    .expect("Couldn’t save the render progress");
+    println!();
     // println!("{}", c.to_ppm());
     c.save(Path::new("out/test.png"))
         .expect("Couldn’t save the png");
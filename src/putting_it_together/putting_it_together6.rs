@@ -0,0 +1,102 @@
+use std::f64::consts::PI;
+use std::io::Write;
+use std::path::Path;
+
+use crate::canvas::{Canvas, Dither, OutputTransform, ToneMap};
+use crate::color::Color;
+use crate::geo::Sphere;
+use crate::matrix::{AnimatedTransform, Matrix4, Transform};
+use crate::render::{CancellationToken, Filter, Renderer, Sample, SamplePattern, Sampler};
+use crate::scene::{Bokeh, Camera, PointLight};
+use crate::tuple::{Point3, Vector3};
+
+const WIDTH: usize = 480;
+const HEIGHT: usize = 270;
+
+pub fn run() {
+    let mut spheres = vec![];
+    for (i, color) in [
+        Color::new(1., 0.2, 0.2),
+        Color::new(0.2, 1., 0.2),
+        Color::new(0.2, 0.4, 1.),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut sphere = Sphere::unit();
+        sphere.transform = Transform::new(Matrix4::translation_matrix(
+            -2.5 + 2.5 * i as f64,
+            0.,
+            3. * i as f64,
+        ));
+        sphere.material.color = color;
+        spheres.push(sphere);
+    }
+    // the red sphere rolls to the right while the shutter is open
+    let resting = spheres[0].transform.matrix();
+    let rolling = Matrix4::rotation_z_matrix(-PI / 2.).translate(0.8, 0., 0.) * resting;
+    spheres[0].motion = Some(AnimatedTransform::new(resting, rolling, 0., 1.));
+    let light = PointLight::new(Point3::point(-10., 10., -10.), Color::white());
+
+    // focus on the middle sphere with an hexagonal aperture
+    let mut camera = Camera::new(WIDTH, HEIGHT, PI / 3.);
+    let from = Point3::point(0., 1.5, -8.);
+    let to = Point3::point(0., 0., 3.);
+    camera.transform = Transform::new(Matrix4::view_transform(
+        from,
+        to,
+        Vector3::vector(0., 1., 0.),
+    ));
+    camera.aperture = 0.25;
+    camera.focal_distance = (to - from).magnitude();
+    camera.bokeh = Bokeh::Polygon {
+        blades: 6,
+        rotation: 0.,
+    };
+    camera.shutter_close = 1.;
+
+    let shader = |sample: &Sample| {
+        let ray = match camera.ray_for_sample(sample) {
+            Some(ray) => ray,
+            None => return Color::black(),
+        };
+        let mut closest: Option<(f64, &Sphere)> = None;
+        for sphere in &spheres {
+            if let Some(hit) = sphere.intersections(&ray).hit() {
+                if closest.is_none_or(|(t, _)| hit.t < t) {
+                    closest = Some((hit.t, sphere));
+                }
+            }
+        }
+        match closest {
+            Some((t, sphere)) => {
+                let position = ray.position(t);
+                let normal = sphere.at_time(ray.time).normal_at(position);
+                sphere
+                    .material
+                    .lighting(light, position, -ray.direction, normal)
+            }
+            None => Color::new(0.1, 0.1, 0.15),
+        }
+    };
+
+    let mut renderer = Renderer::new();
+    renderer.sampler = Sampler::new(32, SamplePattern::Sobol, Filter::gaussian());
+    let mut c = Canvas::new(WIDTH, HEIGHT, Color::black());
+    renderer
+        .render(
+            &mut c,
+            shader,
+            |progress| {
+                print!("\r{:.0}%", progress.percent());
+                std::io::stdout().flush().ok();
+            },
+            &CancellationToken::new(),
+        )
+        .expect("Couldn’t render the scene");
+    println!();
+    let mut output = OutputTransform::srgb(ToneMap::Aces);
+    output.dither = Dither::BlueNoise;
+    c.save_with(Path::new("out/depth_of_field.png"), &output)
+        .expect("Couldn’t save the png");
+}
@@ -0,0 +1,105 @@
+use std::f64::consts::PI;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::geo::Sphere;
+use crate::matrix::{Matrix4, Transform};
+use crate::render::{
+    CancellationToken, Filter, Renderer, Sample, SamplePattern, Sampler, Sequence,
+};
+use crate::scene::{Camera, Easing, PointLight, Track};
+use crate::tuple::{Point3, Vector3};
+
+const WIDTH: usize = 320;
+const HEIGHT: usize = 180;
+
+pub fn run() {
+    // the camera swings around the spheres while the light fades in
+    let camera_position = Track::new(Point3::point(-6., 1.5, -6.))
+        .key(0., Point3::point(-6., 1.5, -6.), Easing::EaseInOut)
+        .key(2., Point3::point(6., 3., -6.), Easing::Linear);
+    let light_intensity = Track::new(Color::new(0.1, 0.1, 0.1))
+        .key(0.5, Color::new(0.1, 0.1, 0.1), Easing::EaseOut)
+        .key(1.5, Color::white(), Easing::Linear);
+    // the red sphere bounces up and turns a full half turn
+    let bounce = Track::new(Matrix4::translation_matrix(-2., 0., 0.))
+        .key(
+            0.,
+            Matrix4::translation_matrix(-2., 0., 0.),
+            Easing::EaseOut,
+        )
+        .key(
+            1.,
+            Matrix4::rotation_y_matrix(PI / 2.).translate(-2., 2., 0.),
+            Easing::EaseIn,
+        )
+        .key(
+            2.,
+            Matrix4::rotation_y_matrix(PI).translate(-2., 0., 0.),
+            Easing::Linear,
+        );
+
+    let scene = |time: f64| {
+        let mut red = Sphere::unit();
+        red.transform = Transform::new(bounce.at(time));
+        red.material.color = Color::new(1., 0.2, 0.2);
+        let mut blue = Sphere::unit();
+        blue.transform = Transform::new(Matrix4::translation_matrix(2., 0., 0.));
+        blue.material.color = Color::new(0.2, 0.4, 1.);
+        let spheres = [red, blue];
+        let light = PointLight::new(Point3::point(-10., 10., -10.), light_intensity.at(time));
+        let mut camera = Camera::new(WIDTH, HEIGHT, PI / 3.);
+        camera.transform = Transform::new(Matrix4::view_transform(
+            camera_position.at(time),
+            Point3::point(0., 0.5, 0.),
+            Vector3::vector(0., 1., 0.),
+        ));
+
+        move |sample: &Sample| {
+            let ray = match camera.ray_for_sample(sample) {
+                Some(ray) => ray,
+                None => return Color::black(),
+            };
+            let mut closest: Option<(f64, &Sphere)> = None;
+            for sphere in &spheres {
+                if let Some(hit) = sphere.intersections(&ray).hit() {
+                    if closest.is_none_or(|(t, _)| hit.t < t) {
+                        closest = Some((hit.t, sphere));
+                    }
+                }
+            }
+            match closest {
+                Some((t, sphere)) => {
+                    let position = ray.position(t);
+                    let normal = sphere.normal_at(position);
+                    sphere
+                        .material
+                        .lighting(light, position, -ray.direction, normal)
+                }
+                None => Color::new(0.1, 0.1, 0.15),
+            }
+        }
+    };
+
+    let mut renderer = Renderer::new();
+    renderer.sampler = Sampler::new(4, SamplePattern::Jittered, Filter::Box);
+    let mut sequence = Sequence::new(1, 49, PathBuf::from("out/animation"));
+    sequence.step = 2;
+    fs::create_dir_all(&sequence.directory).expect("Couldn’t create the frames directory");
+    sequence
+        .render(
+            &renderer,
+            WIDTH,
+            HEIGHT,
+            scene,
+            |frame, progress| {
+                print!("\rframe {} {:.0}%", frame, progress.percent());
+                std::io::stdout().flush().ok();
+            },
+            &CancellationToken::new(),
+        )
+        .expect("Couldn’t render the animation");
+    println!();
+}
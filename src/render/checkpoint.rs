@@ -0,0 +1,239 @@
+use crate::canvas::{Canvas, Pixel};
+use crate::color::Color;
+use crate::render::Tile;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"SCINTCKP";
+const VERSION: u32 = 3;
+// far above any render, keeps a corrupt header from asking for terabytes
+const MAX_PIXELS: usize = 1 << 30;
+
+// the state of an interrupted render: which tiles are finished and the
+// float buffer they were written into
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint<P = Color> {
+    pub tile_size: usize,
+    pub seed: u64,
+    pub done: Vec<bool>,
+    pub canvas: Canvas<P>,
+}
+
+impl<P: Pixel> Checkpoint<P> {
+    pub fn new(canvas: Canvas<P>, tile_size: usize, seed: u64, done: Vec<bool>) -> Self {
+        assert_eq!(
+            done.len(),
+            Tile::grid(canvas.width, canvas.height, tile_size).len()
+        );
+        Self {
+            tile_size,
+            seed,
+            done,
+            canvas,
+        }
+    }
+
+    pub fn tiles_done(&self) -> usize {
+        self.done.iter().filter(|done| **done).count()
+    }
+
+    // write to a temporary file first so that killing the process while
+    // saving never leaves a truncated checkpoint behind
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        self.write_to(&mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        fs::rename(tmp_path, path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        for value in [
+            self.canvas.width,
+            self.canvas.height,
+            self.tile_size,
+            self.done.len(),
+            P::CHANNELS,
+        ] {
+            writer.write_all(&(value as u64).to_le_bytes())?;
+        }
+        writer.write_all(&self.seed.to_le_bytes())?;
+        let done = self
+            .done
+            .iter()
+            .map(|done| *done as u8)
+            .collect::<Vec<u8>>();
+        writer.write_all(&done)?;
+        // store the raw bits so that a resumed render is bit identical
+        for pixel in self.canvas.pixels() {
+            for channel in pixel.channels() {
+                writer.write_all(&channel.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a checkpoint file"));
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        if u32::from_le_bytes(version) != VERSION {
+            return Err(invalid_data("unsupported checkpoint version"));
+        }
+        let width = read_usize(reader)?;
+        let height = read_usize(reader)?;
+        let tile_size = read_usize(reader)?;
+        let tile_count = read_usize(reader)?;
+        if read_usize(reader)? != P::CHANNELS {
+            return Err(invalid_data("checkpoint of another pixel type"));
+        }
+        let mut seed = [0u8; 8];
+        reader.read_exact(&mut seed)?;
+        let pixel_count = width
+            .checked_mul(height)
+            .filter(|count| *count <= MAX_PIXELS)
+            .ok_or_else(|| invalid_data("image too large"))?;
+        // the size of Tile::grid without building it
+        if tile_size == 0 || tile_count != width.div_ceil(tile_size) * height.div_ceil(tile_size) {
+            return Err(invalid_data("inconsistent tile layout"));
+        }
+
+        let mut done = vec![0u8; tile_count];
+        reader.read_exact(&mut done)?;
+        // read through `take` so that the buffer only grows with the bytes
+        // actually in the stream, a truncated file fails before allocating
+        // the whole image
+        let byte_count = (pixel_count * P::CHANNELS * 8) as u64;
+        let mut bytes = vec![];
+        reader.take(byte_count).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != byte_count {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let pixels = bytes
+            .chunks_exact(P::CHANNELS * 8)
+            .map(|pixel| {
+                let channels = pixel
+                    .chunks_exact(8)
+                    .map(|channel| f64::from_le_bytes(channel.try_into().unwrap()))
+                    .collect::<Vec<_>>();
+                P::from_channels(&channels)
+            })
+            .collect();
+
+        Ok(Self {
+            tile_size,
+            seed: u64::from_le_bytes(seed),
+            done: done.into_iter().map(|done| done != 0).collect(),
+            canvas: Canvas::from_pixels(width, height, pixels),
+        })
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_usize<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    usize::try_from(u64::from_le_bytes(bytes)).map_err(|_| invalid_data("size overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Checkpoint;
+    use crate::canvas::Canvas;
+    use crate::color::{Color, Rgba};
+    use std::io::{Cursor, ErrorKind};
+
+    #[test]
+    fn it_round_trips_through_bytes() {
+        let mut canvas = Canvas::new(5, 3, Color::black());
+        canvas.write_pixel(1, 2, Color::new(0.1, 1.5, -0.3));
+        canvas.write_pixel(4, 0, Color::new(1. / 3., 100., f64::MIN_POSITIVE));
+        let checkpoint =
+            Checkpoint::new(canvas, 2, 99, vec![true, false, true, false, false, true]);
+
+        let mut bytes = vec![];
+        checkpoint.write_to(&mut bytes).unwrap();
+        let read = Checkpoint::read_from(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(read, checkpoint);
+        assert_eq!(read.tiles_done(), 3);
+    }
+
+    #[test]
+    fn it_rejects_files_that_are_not_checkpoints() {
+        let result = Checkpoint::<Color>::read_from(&mut Cursor::new(b"P3\n5 3\n255\n".to_vec()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_rejects_truncated_checkpoints() {
+        let checkpoint = Checkpoint::new(Canvas::new(4, 4, Color::white()), 2, 0, vec![true; 4]);
+        let mut bytes = vec![];
+        checkpoint.write_to(&mut bytes).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        assert!(Checkpoint::<Color>::read_from(&mut Cursor::new(bytes)).is_err());
+    }
+
+    // a valid header up to the seed, with the sizes replaced
+    fn header(width: u64, height: u64, tile_size: u64, tile_count: u64) -> Vec<u8> {
+        let checkpoint = Checkpoint::new(Canvas::new(4, 4, Color::white()), 2, 0, vec![true; 4]);
+        let mut bytes = vec![];
+        checkpoint.write_to(&mut bytes).unwrap();
+        bytes.truncate(12);
+        for value in [width, height, tile_size, tile_count, 3, 0] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn it_rejects_oversized_headers_before_allocating() {
+        for bytes in [
+            // the pixel count overflows
+            header(u64::MAX, 2, 1, 1),
+            // a million tiles on a side
+            header(1 << 20, 1 << 20, 1, 1 << 40),
+            header(4, 4, 0, 0),
+        ] {
+            let error = Checkpoint::<Color>::read_from(&mut Cursor::new(bytes)).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn it_rejects_headers_larger_than_the_file() {
+        // a consistent layout but no pixels after the tiles
+        let mut bytes = header(1 << 14, 1 << 14, 1 << 14, 1);
+        bytes.push(1);
+        let error = Checkpoint::<Color>::read_from(&mut Cursor::new(bytes)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn it_keeps_the_alpha_of_rgba_canvases() {
+        let mut canvas = Canvas::transparent(3, 2);
+        canvas.write_pixel(2, 1, Rgba::new(Color::new(0.1, 0.2, 0.3), 0.25));
+        let checkpoint = Checkpoint::new(canvas, 2, 7, vec![false, true]);
+        let mut bytes = vec![];
+        checkpoint.write_to(&mut bytes).unwrap();
+        assert!(Checkpoint::<Color>::read_from(&mut Cursor::new(bytes.clone())).is_err());
+        let read = Checkpoint::<Rgba>::read_from(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(read, checkpoint);
+    }
+}
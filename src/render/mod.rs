@@ -0,0 +1,17 @@
+mod checkpoint;
+mod progress;
+mod random;
+mod renderer;
+mod sampling;
+mod sequence;
+mod stereo;
+mod tile;
+
+pub use checkpoint::Checkpoint;
+pub use progress::{CancellationToken, Progress};
+pub use random::Rng;
+pub use renderer::{RenderError, RenderStatus, Renderer, Snapshot};
+pub use sampling::{Adaptive, Filter, Sample, SamplePattern, Sampler};
+pub use sequence::Sequence;
+pub use stereo::{anaglyph, StereoLayout, StereoRig};
+pub use tile::Tile;
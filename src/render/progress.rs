@@ -0,0 +1,70 @@
+use crate::render::Tile;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    pub tiles_done: usize,
+    pub tiles_total: usize,
+    pub tile: Tile,
+}
+
+impl Progress {
+    pub fn percent(&self) -> f64 {
+        if self.tiles_total == 0 {
+            100.
+        } else {
+            self.tiles_done as f64 * 100. / self.tiles_total as f64
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.tiles_done == self.tiles_total
+    }
+}
+
+// cheap to clone handle shared between the caller and the render workers
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CancellationToken, Progress};
+    use crate::render::Tile;
+
+    #[test]
+    fn it_computes_the_percentage_of_tiles_done() {
+        let progress = Progress {
+            tiles_done: 3,
+            tiles_total: 12,
+            tile: Tile::new(0, 0, 16, 16),
+        };
+        assert_abs_diff_eq!(progress.percent(), 25.);
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn it_shares_the_cancellation_between_clones() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,93 @@
+// SplitMix64, small and good enough to decorrelate the sample patterns
+// while keeping every render reproducible under a seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    // every pixel gets its own stream so that the result does not depend
+    // on the order in which the tiles are rendered
+    pub fn for_pixel(seed: u64, x: usize, y: usize) -> Self {
+        let mut rng = Self::new(
+            seed ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F),
+        );
+        rng.next_u64();
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn it_is_deterministic_under_a_seed() {
+        let mut rng1 = Rng::new(42);
+        let mut rng2 = Rng::new(42);
+        let mut rng3 = Rng::new(43);
+        let values1 = (0..8).map(|_| rng1.next_u64()).collect::<Vec<_>>();
+        let values2 = (0..8).map(|_| rng2.next_u64()).collect::<Vec<_>>();
+        let values3 = (0..8).map(|_| rng3.next_u64()).collect::<Vec<_>>();
+        assert_eq!(values1, values2);
+        assert_ne!(values1, values3);
+    }
+
+    #[test]
+    fn it_generates_floats_in_the_unit_interval() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let value = rng.next_f64();
+            assert!((0. ..1.).contains(&value));
+        }
+    }
+
+    #[test]
+    fn it_shuffles_without_losing_items() {
+        let mut items = (0..32).collect::<Vec<_>>();
+        Rng::new(3).shuffle(&mut items);
+        assert_ne!(items, (0..32).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn it_gives_neighbouring_pixels_different_streams() {
+        let a = Rng::for_pixel(1, 10, 20).next_u64();
+        let b = Rng::for_pixel(1, 11, 20).next_u64();
+        let c = Rng::for_pixel(1, 10, 21).next_u64();
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a, Rng::for_pixel(1, 10, 20).next_u64());
+    }
+}
@@ -0,0 +1,586 @@
+use crate::canvas::{Canvas, Pixel};
+use crate::color::Color;
+use crate::render::{CancellationToken, Checkpoint, Progress, Sample, Sampler, Tile};
+use image::ImageError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// periodically write the render state to disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub interval: Duration,
+}
+
+impl Snapshot {
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        Self { path, interval }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenderStatus {
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    Image(ImageError),
+    Io(io::Error),
+    CheckpointMismatch,
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::Image(e) => write!(f, "failed to save the snapshot: {}", e),
+            RenderError::Io(e) => write!(f, "failed to save the checkpoint: {}", e),
+            RenderError::CheckpointMismatch => {
+                write!(
+                    f,
+                    "the checkpoint does not match the canvas, tile size or seed"
+                )
+            }
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+impl From<ImageError> for RenderError {
+    fn from(e: ImageError) -> Self {
+        RenderError::Image(e)
+    }
+}
+
+impl From<io::Error> for RenderError {
+    fn from(e: io::Error) -> Self {
+        RenderError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Renderer {
+    pub tile_size: usize,
+    pub threads: usize,
+    pub sampler: Sampler,
+    pub snapshot: Option<Snapshot>,
+    pub checkpoint: Option<Snapshot>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self {
+            tile_size: 32,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            sampler: Sampler::default(),
+            snapshot: None,
+            checkpoint: None,
+        }
+    }
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // render the canvas tile by tile, calling `shader` for every sample and
+    // `on_progress` each time a tile is written back to the canvas
+    pub fn render<C, S, P>(
+        &self,
+        canvas: &mut Canvas<C>,
+        shader: S,
+        on_progress: P,
+        token: &CancellationToken,
+    ) -> Result<RenderStatus, RenderError>
+    where
+        C: Pixel,
+        S: Fn(&Sample) -> C + Sync,
+        P: FnMut(&Progress),
+    {
+        let tile_count = Tile::grid(canvas.width, canvas.height, self.tile_size).len();
+        self.render_tiles(
+            canvas,
+            None,
+            vec![false; tile_count],
+            shader,
+            on_progress,
+            token,
+        )
+    }
+
+    // same as `render` and also paint in `sample_map` the number of samples
+    // spent on every pixel, from black for none to white for the maximum
+    pub fn render_with_sample_map<C, S, P>(
+        &self,
+        canvas: &mut Canvas<C>,
+        sample_map: &mut Canvas,
+        shader: S,
+        on_progress: P,
+        token: &CancellationToken,
+    ) -> Result<RenderStatus, RenderError>
+    where
+        C: Pixel,
+        S: Fn(&Sample) -> C + Sync,
+        P: FnMut(&Progress),
+    {
+        assert!(sample_map.width == canvas.width && sample_map.height == canvas.height);
+        let tile_count = Tile::grid(canvas.width, canvas.height, self.tile_size).len();
+        self.render_tiles(
+            canvas,
+            Some(sample_map),
+            vec![false; tile_count],
+            shader,
+            on_progress,
+            token,
+        )
+    }
+
+    // continue an interrupted render, only the tiles that were not done when
+    // the checkpoint was written are rendered again
+    pub fn resume<C, S, P>(
+        &self,
+        canvas: &mut Canvas<C>,
+        checkpoint: &Checkpoint<C>,
+        shader: S,
+        on_progress: P,
+        token: &CancellationToken,
+    ) -> Result<RenderStatus, RenderError>
+    where
+        C: Pixel,
+        S: Fn(&Sample) -> C + Sync,
+        P: FnMut(&Progress),
+    {
+        if checkpoint.tile_size != self.tile_size
+            || checkpoint.seed != self.sampler.seed
+            || checkpoint.canvas.width != canvas.width
+            || checkpoint.canvas.height != canvas.height
+        {
+            return Err(RenderError::CheckpointMismatch);
+        }
+        *canvas = checkpoint.canvas.clone();
+        self.render_tiles(
+            canvas,
+            None,
+            checkpoint.done.clone(),
+            shader,
+            on_progress,
+            token,
+        )
+    }
+
+    fn render_tiles<C, S, P>(
+        &self,
+        canvas: &mut Canvas<C>,
+        mut sample_map: Option<&mut Canvas>,
+        mut done: Vec<bool>,
+        shader: S,
+        mut on_progress: P,
+        token: &CancellationToken,
+    ) -> Result<RenderStatus, RenderError>
+    where
+        C: Pixel,
+        S: Fn(&Sample) -> C + Sync,
+        P: FnMut(&Progress),
+    {
+        let tiles = Tile::grid(canvas.width, canvas.height, self.tile_size);
+        let pending = (0..tiles.len()).filter(|i| !done[*i]).collect::<Vec<_>>();
+        let next_tile = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel::<(usize, Vec<(C, usize)>)>();
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.max(1) {
+                let sender = sender.clone();
+                let (tiles, pending, next_tile, sampler, shader) =
+                    (&tiles, &pending, &next_tile, &self.sampler, &shader);
+                scope.spawn(move || {
+                    while !token.is_cancelled() {
+                        let next = next_tile.fetch_add(1, Ordering::Relaxed);
+                        if next >= pending.len() {
+                            break;
+                        }
+                        let index = pending[next];
+                        let colors = tiles[index]
+                            .pixels()
+                            .map(|(x, y)| sampler.shade_pixel(x, y, shader))
+                            .collect();
+                        // the receiver is gone when the render bailed out early
+                        if sender.send((index, colors)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            let mut tiles_done = tiles.len() - pending.len();
+            let mut last_snapshot = Instant::now();
+            let mut last_checkpoint = Instant::now();
+            let max_samples = self.sampler.max_samples() as f64;
+            for (index, colors) in receiver {
+                if token.is_cancelled() {
+                    break;
+                }
+                let tile = tiles[index];
+                for ((x, y), (color, count)) in tile.pixels().zip(colors) {
+                    canvas.write_pixel(x, y, color);
+                    if let Some(sample_map) = sample_map.as_deref_mut() {
+                        let level = count as f64 / max_samples;
+                        sample_map.write_pixel(x, y, Color::new(level, level, level));
+                    }
+                }
+                done[index] = true;
+                tiles_done += 1;
+                on_progress(&Progress {
+                    tiles_done,
+                    tiles_total: tiles.len(),
+                    tile,
+                });
+
+                if let Some(snapshot) = &self.snapshot {
+                    if last_snapshot.elapsed() >= snapshot.interval {
+                        C::save_canvas(canvas, &snapshot.path)?;
+                        last_snapshot = Instant::now();
+                    }
+                }
+                if let Some(checkpoint) = &self.checkpoint {
+                    if last_checkpoint.elapsed() >= checkpoint.interval {
+                        self.save_checkpoint(canvas, &done, checkpoint)?;
+                        last_checkpoint = Instant::now();
+                    }
+                }
+            }
+
+            if tiles_done == tiles.len() {
+                Ok(RenderStatus::Completed)
+            } else {
+                // keep the work done so far so that the render can be resumed
+                if let Some(checkpoint) = &self.checkpoint {
+                    self.save_checkpoint(canvas, &done, checkpoint)?;
+                }
+                Ok(RenderStatus::Cancelled)
+            }
+        })
+    }
+
+    fn save_checkpoint<C: Pixel>(
+        &self,
+        canvas: &Canvas<C>,
+        done: &[bool],
+        checkpoint: &Snapshot,
+    ) -> Result<(), RenderError> {
+        Checkpoint::new(
+            canvas.clone(),
+            self.tile_size,
+            self.sampler.seed,
+            done.to_vec(),
+        )
+        .save(&checkpoint.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderError, RenderStatus, Renderer, Snapshot};
+    use crate::canvas::Canvas;
+    use crate::color::{Color, Rgba};
+    use crate::float::Float;
+    use crate::geo::{Intersectable, Sphere};
+    use crate::matrix::{Matrix4, Transform};
+    use crate::render::{
+        Adaptive, CancellationToken, Checkpoint, Filter, Sample, SamplePattern, Sampler,
+    };
+    use crate::scene::{Camera, PointLight};
+    use crate::tuple::{Point3, Vector3};
+    use std::env;
+    use std::time::Duration;
+
+    fn gradient(sample: &Sample) -> Color {
+        Color::new(sample.x / 10., sample.y / 10., 0.)
+    }
+
+    #[test]
+    fn it_renders_every_pixel() {
+        let renderer = Renderer {
+            tile_size: 4,
+            threads: 3,
+            sampler: Sampler::default(),
+            snapshot: None,
+            checkpoint: None,
+        };
+        let mut canvas = Canvas::new(10, 7, Color::black());
+        let status = renderer
+            .render(&mut canvas, gradient, |_| {}, &CancellationToken::new())
+            .unwrap();
+        assert_eq!(status, RenderStatus::Completed);
+        for y in 0..7 {
+            for x in 0..10 {
+                assert_eq!(
+                    canvas.read_pixel(x, y),
+                    &gradient(&Sample::new(x as f64 + 0.5, y as f64 + 0.5))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_reports_the_progress_of_every_tile() {
+        let renderer = Renderer {
+            tile_size: 5,
+            threads: 2,
+            sampler: Sampler::default(),
+            snapshot: None,
+            checkpoint: None,
+        };
+        let mut canvas = Canvas::new(10, 10, Color::black());
+        let mut reports = vec![];
+        renderer
+            .render(
+                &mut canvas,
+                gradient,
+                |progress| reports.push(*progress),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_eq!(reports.len(), 4);
+        assert_eq!(
+            reports.iter().map(|p| p.tiles_done).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert!(reports[3].is_complete());
+    }
+
+    #[test]
+    fn it_does_not_render_anything_when_cancelled_upfront() {
+        let renderer = Renderer::new();
+        let mut canvas = Canvas::new(10, 10, Color::black());
+        let token = CancellationToken::new();
+        token.cancel();
+        let status = renderer
+            .render(&mut canvas, |_| Color::white(), |_| {}, &token)
+            .unwrap();
+        assert_eq!(status, RenderStatus::Cancelled);
+        assert_eq!(canvas.read_pixel(0, 0), &Color::black());
+    }
+
+    #[test]
+    fn it_stops_when_cancelled_during_the_render() {
+        let renderer = Renderer {
+            tile_size: 1,
+            threads: 1,
+            sampler: Sampler::default(),
+            snapshot: None,
+            checkpoint: None,
+        };
+        let mut canvas = Canvas::new(10, 10, Color::black());
+        let token = CancellationToken::new();
+        let status = renderer
+            .render(
+                &mut canvas,
+                |_| Color::white(),
+                |progress| {
+                    if progress.tiles_done == 10 {
+                        token.cancel()
+                    }
+                },
+                &token,
+            )
+            .unwrap();
+        assert_eq!(status, RenderStatus::Cancelled);
+        assert_eq!(canvas.read_pixel(9, 9), &Color::black());
+    }
+
+    #[test]
+    fn it_resumes_from_a_checkpoint_to_the_same_image() {
+        let path = env::temp_dir().join(format!("scintilla-{}.ckpt", std::process::id()));
+        let mut sampler = Sampler::new(5, SamplePattern::Jittered, Filter::mitchell());
+        sampler.seed = 1234;
+        let renderer = Renderer {
+            tile_size: 3,
+            threads: 2,
+            sampler,
+            snapshot: None,
+            checkpoint: Some(Snapshot::new(path.clone(), Duration::from_secs(3600))),
+        };
+        let shader = |sample: &Sample| {
+            Color::new(sample.x.abs().sqrt() / 3., 1. / (sample.y.abs() + 3.), 0.7)
+        };
+
+        let mut expected = Canvas::new(10, 10, Color::black());
+        renderer
+            .render(&mut expected, shader, |_| {}, &CancellationToken::new())
+            .unwrap();
+
+        let mut canvas = Canvas::new(10, 10, Color::black());
+        let token = CancellationToken::new();
+        let status = renderer
+            .render(
+                &mut canvas,
+                shader,
+                |progress| {
+                    if progress.tiles_done == 7 {
+                        token.cancel()
+                    }
+                },
+                &token,
+            )
+            .unwrap();
+        assert_eq!(status, RenderStatus::Cancelled);
+
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.tiles_done(), 7);
+        let mut resumed = Canvas::new(10, 10, Color::black());
+        let mut reports = vec![];
+        let status = renderer
+            .resume(
+                &mut resumed,
+                &checkpoint,
+                shader,
+                |progress| reports.push(progress.tiles_done),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(status, RenderStatus::Completed);
+        assert_eq!(reports, (8..=16).collect::<Vec<_>>());
+        assert_eq!(resumed, expected);
+    }
+
+    #[test]
+    fn it_refuses_to_resume_from_a_checkpoint_with_another_layout() {
+        let renderer = Renderer::new();
+        let checkpoint = Checkpoint::new(Canvas::new(4, 4, Color::black()), 2, 0, vec![false; 4]);
+        let mut canvas = Canvas::new(4, 4, Color::black());
+        let result = renderer.resume(
+            &mut canvas,
+            &checkpoint,
+            |_| Color::white(),
+            |_| {},
+            &CancellationToken::new(),
+        );
+        assert!(matches!(result, Err(RenderError::CheckpointMismatch)));
+    }
+
+    #[test]
+    fn it_maps_the_samples_spent_on_every_pixel() {
+        let mut sampler = Sampler::new(4, SamplePattern::Jittered, Filter::Box);
+        sampler.adaptive = Some(Adaptive::new(16, 0.01));
+        let renderer = Renderer {
+            tile_size: 4,
+            threads: 2,
+            sampler,
+            snapshot: None,
+            checkpoint: None,
+        };
+        // a vertical edge running through the middle of pixel 5
+        let edge = |sample: &Sample| {
+            if sample.x < 5.5 {
+                Color::white()
+            } else {
+                Color::black()
+            }
+        };
+        let mut canvas = Canvas::new(8, 2, Color::black());
+        let mut sample_map = Canvas::new(8, 2, Color::black());
+        renderer
+            .render_with_sample_map(
+                &mut canvas,
+                &mut sample_map,
+                edge,
+                |_| {},
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_abs_diff_eq!(sample_map.read_pixel(0, 0).r, 0.25);
+        assert_abs_diff_eq!(sample_map.read_pixel(5, 1).r, 1.);
+        assert_abs_diff_eq!(sample_map.read_pixel(7, 1).r, 0.25);
+        assert_abs_diff_eq!(canvas.read_pixel(5, 0).r, 0.5, epsilon = 0.2);
+    }
+
+    #[test]
+    fn it_leaves_pixels_transparent_where_nothing_is_hit() {
+        let mut renderer = Renderer::new();
+        renderer.sampler = Sampler::new(16, SamplePattern::Regular, Filter::Box);
+        // a disc of radius 3 around the center of the canvas
+        let disc = |sample: &Sample| {
+            let (dx, dy) = (sample.x - 4., sample.y - 4.);
+            if dx * dx + dy * dy < 9. {
+                Rgba::opaque(Color::white())
+            } else {
+                Rgba::transparent()
+            }
+        };
+        let mut canvas = Canvas::transparent(8, 8);
+        renderer
+            .render(&mut canvas, disc, |_| {}, &CancellationToken::new())
+            .unwrap();
+        assert_abs_diff_eq!(*canvas.read_pixel(4, 4), Rgba::opaque(Color::white()));
+        assert_abs_diff_eq!(*canvas.read_pixel(0, 0), Rgba::transparent());
+        let edge = canvas.read_pixel(1, 4);
+        assert!(edge.alpha > 0. && edge.alpha < 1.);
+        assert_abs_diff_eq!(edge.straight(), Color::white(), epsilon = 1e-12);
+    }
+
+    // a lit sphere traced in the precision of T
+    fn sphere_shader<T: Float>() -> impl Fn(&Sample) -> Color<T> + Sync {
+        let f = T::from_f64;
+        let mut sphere = Sphere::unit();
+        sphere.transform = Transform::new(Matrix4::scale_matrix(f(1.5), f(1.), f(1.)));
+        sphere.material.color = Color::new(f(1.), f(0.2), f(0.6));
+        let light = PointLight::new(Point3::point(f(-10.), f(10.), f(-10.)), Color::white());
+        let mut camera = Camera::new(24, 16, f(std::f64::consts::FRAC_PI_3));
+        camera.transform = Transform::new(Matrix4::view_transform(
+            Point3::point(f(0.), f(1.), f(-5.)),
+            Point3::origin(),
+            Vector3::vector(f(0.), f(1.), f(0.)),
+        ));
+        move |sample| {
+            let Some(ray) = camera.ray_for_sample(sample) else {
+                return Color::black();
+            };
+            let xs = ray.intersect(&Intersectable::Sphere(sphere));
+            let Some(hit) = xs.hit() else {
+                return Color::black();
+            };
+            let point = ray.position(hit.t);
+            let normal = hit.intersectable.normal_at(point);
+            hit.intersectable
+                .get_material()
+                .lighting(light, point, -ray.direction, normal)
+        }
+    }
+
+    #[test]
+    fn it_renders_scenes_traced_in_single_precision() {
+        let renderer = Renderer::new();
+        let token = CancellationToken::new();
+        let mut double = Canvas::new(24, 16, Color::black());
+        renderer
+            .render(&mut double, sphere_shader::<f64>(), |_| {}, &token)
+            .unwrap();
+        let mut single = Canvas::new(24, 16, Color::black());
+        let shader = sphere_shader::<f32>();
+        renderer
+            .render(
+                &mut single,
+                |sample| Color::from(shader(sample)),
+                |_| {},
+                &token,
+            )
+            .unwrap();
+        assert!(double.pixels().iter().any(|color| color.r > 0.5));
+        for (single, double) in single.pixels().iter().zip(double.pixels()) {
+            assert_abs_diff_eq!(single, double, epsilon = 1e-4);
+        }
+    }
+}
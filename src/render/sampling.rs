@@ -0,0 +1,603 @@
+use crate::canvas::Pixel;
+use crate::render::Rng;
+
+// a position on the image plane in continuous raster coordinates, the
+// centre of pixel (x, y) is at (x + 0.5, y + 0.5), a position on the
+// camera lens in the unit square and an instant of the shutter interval
+// in [0, 1)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sample {
+    pub x: f64,
+    pub y: f64,
+    pub lens_u: f64,
+    pub lens_v: f64,
+    pub time: f64,
+}
+
+impl Sample {
+    // through the centre of the lens
+    pub fn new(x: f64, y: f64) -> Self {
+        Self::with_lens(x, y, 0.5, 0.5)
+    }
+
+    pub fn with_lens(x: f64, y: f64, lens_u: f64, lens_v: f64) -> Self {
+        Self {
+            x,
+            y,
+            lens_u,
+            lens_v,
+            time: 0.,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SamplePattern {
+    Regular,
+    Jittered,
+    Halton,
+    Sobol,
+}
+
+impl SamplePattern {
+    // `count` points in the unit square, from the `start`th one of the
+    // sequence. Halton and Sobol only draw their scrambling from `rng`, so
+    // the same state continues the same sequence.
+    pub fn points(&self, start: usize, count: usize, rng: &mut Rng) -> Vec<(f64, f64)> {
+        match self {
+            SamplePattern::Regular | SamplePattern::Jittered => {
+                let cols = (count as f64).sqrt().ceil() as usize;
+                let rows = (count + cols - 1) / cols.max(1);
+                // the grid would repeat the same points in every batch, the
+                // later ones are jittered instead
+                let jittered = *self == SamplePattern::Jittered || start > 0;
+                (0..count)
+                    .map(|i| {
+                        let (jx, jy) = if jittered {
+                            (rng.next_f64(), rng.next_f64())
+                        } else {
+                            (0.5, 0.5)
+                        };
+                        (
+                            ((i % cols) as f64 + jx) / cols as f64,
+                            ((i / cols) as f64 + jy) / rows as f64,
+                        )
+                    })
+                    .collect()
+            }
+            SamplePattern::Halton => {
+                // Cranley-Patterson rotation so that pixels don't share the
+                // exact same points
+                let (ox, oy) = (rng.next_f64(), rng.next_f64());
+                (start..start + count)
+                    .map(|i| {
+                        (
+                            (radical_inverse(i as u64 + 1, 2) + ox).fract(),
+                            (radical_inverse(i as u64 + 1, 3) + oy).fract(),
+                        )
+                    })
+                    .collect()
+            }
+            SamplePattern::Sobol => {
+                // random digit scrambling keeps the stratification
+                let (sx, sy) = (rng.next_u32(), rng.next_u32());
+                (start as u32..(start + count) as u32)
+                    .map(|i| {
+                        (
+                            to_unit((i.reverse_bits()) ^ sx),
+                            to_unit(sobol_second_dimension(i) ^ sy),
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn is_sequence(&self) -> bool {
+        matches!(self, SamplePattern::Halton | SamplePattern::Sobol)
+    }
+}
+
+fn radical_inverse(mut index: u64, base: u64) -> f64 {
+    let inv_base = 1. / base as f64;
+    let mut factor = inv_base;
+    let mut result = 0.;
+    while index > 0 {
+        result += (index % base) as f64 * factor;
+        index /= base;
+        factor *= inv_base;
+    }
+    result
+}
+
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let mut direction = 1u32 << 31;
+    let mut result = 0;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= direction
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+fn to_unit(value: u32) -> f64 {
+    value as f64 / (1u64 << 32) as f64
+}
+
+// pixel reconstruction filters, all of them are separable
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    Box,
+    Tent,
+    Gaussian { alpha: f64 },
+    Mitchell { b: f64, c: f64 },
+}
+
+impl Filter {
+    pub fn gaussian() -> Self {
+        Filter::Gaussian { alpha: 2. }
+    }
+
+    pub fn mitchell() -> Self {
+        Filter::Mitchell {
+            b: 1. / 3.,
+            c: 1. / 3.,
+        }
+    }
+
+    // half width of the filter support, in pixels
+    pub fn radius(&self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.,
+            Filter::Gaussian { .. } => 1.5,
+            Filter::Mitchell { .. } => 2.,
+        }
+    }
+
+    // weight of a sample at (dx, dy) from the pixel centre
+    pub fn weight(&self, dx: f64, dy: f64) -> f64 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    fn weight_1d(&self, d: f64) -> f64 {
+        let radius = self.radius();
+        let d = d.abs();
+        if d > radius {
+            return 0.;
+        }
+        match *self {
+            Filter::Box => 1.,
+            Filter::Tent => 1. - d / radius,
+            Filter::Gaussian { alpha } => {
+                ((-alpha * d * d).exp() - (-alpha * radius * radius).exp()).max(0.)
+            }
+            Filter::Mitchell { b, c } => {
+                // the Mitchell-Netravali kernel is defined on [-2, 2]
+                let x = 2. * d / radius;
+                if x < 1. {
+                    ((12. - 9. * b - 6. * c) * x.powi(3)
+                        + (-18. + 12. * b + 6. * c) * x.powi(2)
+                        + (6. - 2. * b))
+                        / 6.
+                } else {
+                    ((-b - 6. * c) * x.powi(3)
+                        + (6. * b + 30. * c) * x.powi(2)
+                        + (-12. * b - 48. * c) * x
+                        + (8. * b + 24. * c))
+                        / 6.
+                }
+            }
+        }
+    }
+}
+
+const FILTER_BINS: usize = 64;
+
+// the samples are drawn along each axis in proportion to the absolute
+// value of the filter, tabulated over its support, so that they land where
+// the filter matters and only carry its sign. Spreading them uniformly
+// leaves few of them in the positive lobe of Mitchell at low sample counts,
+// and the sum of the weights can then get close to zero or negative.
+#[derive(Debug, Clone, PartialEq)]
+struct FilterDistribution {
+    radius: f64,
+    cdf: [f64; FILTER_BINS + 1],
+}
+
+impl FilterDistribution {
+    fn new(filter: &Filter) -> Self {
+        let radius = filter.radius();
+        let step = 2. * radius / FILTER_BINS as f64;
+        let mut cdf = [0.; FILTER_BINS + 1];
+        for bin in 0..FILTER_BINS {
+            let centre = -radius + (bin as f64 + 0.5) * step;
+            cdf[bin + 1] = cdf[bin] + filter.weight_1d(centre).abs();
+        }
+        let total = cdf[FILTER_BINS];
+        cdf.iter_mut().for_each(|value| *value /= total);
+        Self { radius, cdf }
+    }
+
+    // the offset from the pixel centre for u in [0, 1)
+    fn offset(&self, u: f64) -> f64 {
+        let bin = self
+            .cdf
+            .partition_point(|value| *value <= u)
+            .clamp(1, FILTER_BINS)
+            - 1;
+        let width = self.cdf[bin + 1] - self.cdf[bin];
+        let t = if width > 0. {
+            (u - self.cdf[bin]) / width
+        } else {
+            0.5
+        };
+        (-1. + 2. * (bin as f64 + t) / FILTER_BINS as f64) * self.radius
+    }
+}
+
+// keep sampling a pixel by batches of `samples_per_pixel` until the standard
+// error of its luminance drops below `threshold` or `max_samples` is reached
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Adaptive {
+    pub max_samples: usize,
+    pub threshold: f64,
+}
+
+impl Adaptive {
+    pub fn new(max_samples: usize, threshold: f64) -> Self {
+        Self {
+            max_samples,
+            threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sampler {
+    pub samples_per_pixel: usize,
+    pub pattern: SamplePattern,
+    // private so that it can't change under `distribution`, which is built
+    // with it once rather than for every batch
+    filter: Filter,
+    distribution: FilterDistribution,
+    pub seed: u64,
+    pub adaptive: Option<Adaptive>,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self::new(1, SamplePattern::Regular, Filter::Box)
+    }
+}
+
+impl Sampler {
+    pub fn new(samples_per_pixel: usize, pattern: SamplePattern, filter: Filter) -> Self {
+        Self {
+            samples_per_pixel,
+            pattern,
+            distribution: FilterDistribution::new(&filter),
+            filter,
+            seed: 0,
+            adaptive: None,
+        }
+    }
+
+    pub fn filter(&self) -> Filter {
+        self.filter
+    }
+
+    // the upper bound of samples spent on a single pixel
+    pub fn max_samples(&self) -> usize {
+        let batch = self.samples_per_pixel.max(1);
+        match self.adaptive {
+            Some(adaptive) => adaptive.max_samples.max(batch),
+            None => batch,
+        }
+    }
+
+    // the first batch of samples of a pixel
+    pub fn samples(&self, x: usize, y: usize) -> Vec<(Sample, f64)> {
+        let mut rng = Rng::for_pixel(self.seed, x, y);
+        let scramble = Rng::new(rng.next_u64());
+        self.batch(x, y, 0, &scramble, &mut rng)
+    }
+
+    // the samples are distributed like the filter around the pixel centre
+    // and weighted by its sign, the lens positions and times are stratified
+    // too and shuffled so that they are not correlated with the image
+    // positions. The `index`th batch of a pixel continues the sequence
+    // where the previous one stopped, scrambled the same way by `scramble`.
+    fn batch(
+        &self,
+        x: usize,
+        y: usize,
+        index: usize,
+        scramble: &Rng,
+        rng: &mut Rng,
+    ) -> Vec<(Sample, f64)> {
+        let count = self.samples_per_pixel.max(1);
+        let distribution = &self.distribution;
+        let mut scramble = scramble.clone();
+        let pattern_rng = if self.pattern.is_sequence() {
+            &mut scramble
+        } else {
+            &mut *rng
+        };
+        let points = self.pattern.points(index * count, count, pattern_rng);
+        let mut lens_points = self.pattern.points(index * count, count, pattern_rng);
+        rng.shuffle(&mut lens_points);
+        let mut times = (0..count)
+            .map(|i| (i as f64 + rng.next_f64()) / count as f64)
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut times);
+        points
+            .into_iter()
+            .zip(lens_points)
+            .zip(times)
+            .map(|(((u, v), (lens_u, lens_v)), time)| {
+                let (dx, dy) = (distribution.offset(u), distribution.offset(v));
+                let mut sample =
+                    Sample::with_lens(x as f64 + 0.5 + dx, y as f64 + 0.5 + dy, lens_u, lens_v);
+                sample.time = time;
+                let weight = if self.filter.weight(dx, dy) < 0. {
+                    -1.
+                } else {
+                    1.
+                };
+                (sample, weight)
+            })
+            .collect()
+    }
+
+    // the filtered color of the pixel and the number of samples it took
+    pub fn shade_pixel<P, S>(&self, x: usize, y: usize, shader: S) -> (P, usize)
+    where
+        P: Pixel,
+        S: Fn(&Sample) -> P,
+    {
+        let mut rng = Rng::for_pixel(self.seed, x, y);
+        let scramble = Rng::new(rng.next_u64());
+        let max_samples = self.max_samples();
+        let mut color = P::zero();
+        let mut unfiltered = P::zero();
+        let mut total_weight = 0.;
+        let mut count = 0;
+        // running luminance mean and sum of squared deviations (Welford)
+        let (mut mean, mut m2) = (0., 0.);
+
+        let mut batch = 0;
+        while count < max_samples {
+            for (sample, weight) in self.batch(x, y, batch, &scramble, &mut rng) {
+                if count == max_samples {
+                    break;
+                }
+                let value = shader(&sample);
+                color = color + value * weight;
+                unfiltered = unfiltered + value;
+                total_weight += weight;
+                count += 1;
+                let delta = value.luminance() - mean;
+                mean += delta / count as f64;
+                m2 += delta * (value.luminance() - mean);
+            }
+            batch += 1;
+            match self.adaptive {
+                Some(adaptive) if count > 1 => {
+                    let variance = m2 / (count - 1) as f64;
+                    if (variance / count as f64).sqrt() <= adaptive.threshold {
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+
+        // as many samples in the negative lobes as in the positive one, the
+        // plain average is better than dividing by almost nothing
+        if total_weight < 0.5 {
+            (unfiltered * (1. / count as f64), count)
+        } else {
+            (color * (1. / total_weight), count)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Adaptive, Filter, Sample, SamplePattern, Sampler};
+    use crate::color::Color;
+    use crate::render::Rng;
+
+    #[test]
+    fn it_places_regular_samples_on_a_grid() {
+        let points = SamplePattern::Regular.points(0, 4, &mut Rng::new(0));
+        assert_eq!(
+            points,
+            vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+    }
+
+    #[test]
+    fn it_jitters_samples_within_their_strata() {
+        let points = SamplePattern::Jittered.points(0, 9, &mut Rng::new(3));
+        for (i, (u, v)) in points.into_iter().enumerate() {
+            let (col, row) = ((i % 3) as f64, (i / 3) as f64);
+            assert!(u >= col / 3. && u < (col + 1.) / 3.);
+            assert!(v >= row / 3. && v < (row + 1.) / 3.);
+        }
+    }
+
+    #[test]
+    fn it_generates_low_discrepancy_sequences() {
+        // a 4 points Sobol sequence has exactly one point per quadrant,
+        // whatever the scrambling
+        let points = SamplePattern::Sobol.points(0, 4, &mut Rng::new(11));
+        let mut quadrants = points
+            .iter()
+            .map(|(u, v)| ((u * 2.) as usize, (v * 2.) as usize))
+            .collect::<Vec<_>>();
+        quadrants.sort();
+        assert_eq!(quadrants, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+
+        let halton = SamplePattern::Halton.points(0, 64, &mut Rng::new(11));
+        assert_eq!(halton.len(), 64);
+        assert!(halton
+            .iter()
+            .all(|(u, v)| (0. ..1.).contains(u) && (0. ..1.).contains(v)));
+    }
+
+    #[test]
+    fn it_continues_the_sequence_in_later_batches() {
+        for pattern in [
+            SamplePattern::Regular,
+            SamplePattern::Jittered,
+            SamplePattern::Halton,
+            SamplePattern::Sobol,
+        ] {
+            let sampler = Sampler::new(4, pattern, Filter::Box);
+            let (scramble, mut rng) = (Rng::new(1), Rng::new(2));
+            let first = sampler.batch(0, 0, 0, &scramble, &mut rng);
+            let second = sampler.batch(0, 0, 1, &scramble, &mut rng);
+            for (sample, _) in &first {
+                assert!(second
+                    .iter()
+                    .all(|(other, _)| (sample.x, sample.y) != (other.x, other.y)));
+            }
+        }
+
+        // two batches of two Sobol points are the 4 first points of the
+        // sequence, one per quadrant of the pixel
+        let sampler = Sampler::new(2, SamplePattern::Sobol, Filter::Box);
+        let (scramble, mut rng) = (Rng::new(1), Rng::new(2));
+        let mut quadrants = (0..2)
+            .flat_map(|batch| sampler.batch(0, 0, batch, &scramble, &mut rng))
+            .map(|(sample, _)| ((sample.x * 2.) as usize, (sample.y * 2.) as usize))
+            .collect::<Vec<_>>();
+        quadrants.sort();
+        assert_eq!(quadrants, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn it_samples_deterministically_under_a_seed() {
+        let mut sampler = Sampler::new(8, SamplePattern::Jittered, Filter::Box);
+        sampler.seed = 5;
+        assert_eq!(sampler.samples(3, 4), sampler.samples(3, 4));
+        assert_ne!(sampler.samples(3, 4), sampler.samples(4, 4));
+        let mut other = sampler.clone();
+        other.seed = 6;
+        assert_ne!(sampler.samples(3, 4), other.samples(3, 4));
+    }
+
+    #[test]
+    fn it_stratifies_the_sample_times_over_the_shutter_interval() {
+        let sampler = Sampler::new(8, SamplePattern::Jittered, Filter::Box);
+        let mut strata = sampler
+            .samples(1, 2)
+            .iter()
+            .map(|(sample, _)| (sample.time * 8.) as usize)
+            .collect::<Vec<_>>();
+        strata.sort();
+        assert_eq!(strata, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn it_weights_samples_with_the_filter() {
+        assert_abs_diff_eq!(Filter::Box.weight(0.4, -0.4), 1.);
+        assert_abs_diff_eq!(Filter::Tent.weight(0., 0.), 1.);
+        assert_abs_diff_eq!(Filter::Tent.weight(0.5, 0.), 0.5);
+        assert_abs_diff_eq!(Filter::Tent.weight(1.2, 0.), 0.);
+        assert!(Filter::gaussian().weight(0., 0.) > Filter::gaussian().weight(1., 0.));
+        assert_abs_diff_eq!(Filter::gaussian().weight(1.5, 0.), 0.);
+        assert_abs_diff_eq!(Filter::mitchell().weight(0., 0.), 8. / 9. * 8. / 9.);
+        // the negative lobe sharpens edges
+        assert!(Filter::mitchell().weight(1.5, 0.) < 0.);
+    }
+
+    #[test]
+    fn it_distributes_the_samples_like_the_filter() {
+        let sampler = Sampler::new(4, SamplePattern::Jittered, Filter::mitchell());
+        let (mut positive, mut negative) = (0, 0);
+        for y in 0..32 {
+            for x in 0..32 {
+                let samples = sampler.samples(x, y);
+                let total_weight = samples.iter().map(|(_, weight)| weight).sum::<f64>();
+                if total_weight > 0. {
+                    positive += 1;
+                } else {
+                    negative += 1;
+                }
+                // the centre lobe of Mitchell spans one pixel on each side
+                let central = samples
+                    .iter()
+                    .filter(|(sample, _)| {
+                        (sample.x - x as f64 - 0.5).abs() < 1.
+                            && (sample.y - y as f64 - 0.5).abs() < 1.
+                    })
+                    .count();
+                assert!(central >= 2);
+                let (color, _) = sampler.shade_pixel(x, y, |_| Color::new(0.2, 0.4, 0.6));
+                assert_abs_diff_eq!(color, Color::new(0.2, 0.4, 0.6), epsilon = 1e-12);
+            }
+        }
+        // spread uniformly, about a third of the pixels had no positive total
+        assert!(negative * 20 < positive, "{negative} of 1024 pixels");
+    }
+
+    #[test]
+    fn it_shades_the_centre_of_the_pixel_with_a_single_sample() {
+        let sampler = Sampler::default();
+        let (color, count) = sampler.shade_pixel(2, 3, |sample| Color::new(sample.x, sample.y, 0.));
+        assert_abs_diff_eq!(color, Color::new(2.5, 3.5, 0.));
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn it_anti_aliases_edges() {
+        let sampler = Sampler::new(4, SamplePattern::Regular, Filter::Box);
+        let edge = |sample: &Sample| {
+            if sample.x < 10.5 {
+                Color::white()
+            } else {
+                Color::black()
+            }
+        };
+        assert_abs_diff_eq!(
+            sampler.shade_pixel(10, 0, edge).0,
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_abs_diff_eq!(sampler.shade_pixel(9, 0, edge).0, Color::white());
+    }
+
+    #[test]
+    fn it_stops_sampling_flat_pixels_after_the_first_batch() {
+        let mut sampler = Sampler::new(4, SamplePattern::Jittered, Filter::Box);
+        sampler.adaptive = Some(Adaptive::new(64, 0.01));
+        let (color, count) = sampler.shade_pixel(5, 5, |_| Color::new(0.2, 0.4, 0.6));
+        assert_abs_diff_eq!(color, Color::new(0.2, 0.4, 0.6), epsilon = 1e-12);
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn it_spends_more_samples_on_noisy_pixels() {
+        let mut sampler = Sampler::new(4, SamplePattern::Jittered, Filter::Box);
+        sampler.adaptive = Some(Adaptive::new(64, 0.01));
+        let edge = |sample: &Sample| {
+            if sample.x + sample.y < 11. {
+                Color::white()
+            } else {
+                Color::black()
+            }
+        };
+        let (_, count) = sampler.shade_pixel(5, 5, edge);
+        assert_eq!(count, 64);
+
+        sampler.adaptive = Some(Adaptive::new(64, 0.2));
+        let (_, count) = sampler.shade_pixel(5, 5, edge);
+        assert!(count > 4 && count < 64);
+    }
+}
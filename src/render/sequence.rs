@@ -0,0 +1,153 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::render::{CancellationToken, Progress, RenderError, RenderStatus, Renderer, Sample};
+use std::path::PathBuf;
+
+// a range of frames rendered one after the other to numbered images
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    pub first: usize,
+    pub last: usize,
+    pub step: usize,
+    pub frames_per_second: f64,
+    pub directory: PathBuf,
+}
+
+impl Sequence {
+    pub fn new(first: usize, last: usize, directory: PathBuf) -> Self {
+        Self {
+            first,
+            last,
+            step: 1,
+            frames_per_second: 24.,
+            directory,
+        }
+    }
+
+    // the frame numbers to render, `last` included
+    pub fn frames(&self) -> impl Iterator<Item = usize> {
+        (self.first..=self.last).step_by(self.step.max(1))
+    }
+
+    // the scene time in seconds, frame 1 being at 0
+    pub fn time(&self, frame: usize) -> f64 {
+        (frame as f64 - 1.) / self.frames_per_second
+    }
+
+    pub fn path(&self, frame: usize) -> PathBuf {
+        self.directory.join(format!("frame_{:04}.png", frame))
+    }
+
+    // render and save every frame, `scene` builds the shader for a given time
+    // and `on_progress` is called with the frame number for every tile
+    pub fn render<F, S, P>(
+        &self,
+        renderer: &Renderer,
+        width: usize,
+        height: usize,
+        mut scene: F,
+        mut on_progress: P,
+        token: &CancellationToken,
+    ) -> Result<RenderStatus, RenderError>
+    where
+        F: FnMut(f64) -> S,
+        S: Fn(&Sample) -> Color + Sync,
+        P: FnMut(usize, &Progress),
+    {
+        for frame in self.frames() {
+            let mut canvas = Canvas::new(width, height, Color::black());
+            let status = renderer.render(
+                &mut canvas,
+                scene(self.time(frame)),
+                |progress| on_progress(frame, progress),
+                token,
+            )?;
+            if status == RenderStatus::Cancelled {
+                return Ok(status);
+            }
+            canvas.save(&self.path(frame))?;
+        }
+        Ok(RenderStatus::Completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sequence;
+    use crate::color::Color;
+    use crate::render::{CancellationToken, RenderStatus, Renderer};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn it_steps_through_the_frame_range() {
+        let mut sequence = Sequence::new(1, 10, PathBuf::from("out"));
+        sequence.step = 3;
+        assert_eq!(sequence.frames().collect::<Vec<_>>(), vec![1, 4, 7, 10]);
+    }
+
+    #[test]
+    fn it_converts_frames_to_times() {
+        let mut sequence = Sequence::new(1, 48, PathBuf::from("out"));
+        sequence.frames_per_second = 24.;
+        assert_abs_diff_eq!(sequence.time(1), 0.);
+        assert_abs_diff_eq!(sequence.time(25), 1.);
+    }
+
+    #[test]
+    fn it_numbers_the_frame_files() {
+        let sequence = Sequence::new(1, 10, PathBuf::from("out"));
+        assert_eq!(sequence.path(7), PathBuf::from("out/frame_0007.png"));
+        assert_eq!(sequence.path(12345), PathBuf::from("out/frame_12345.png"));
+    }
+
+    #[test]
+    fn it_renders_every_frame_at_its_time() {
+        let directory = env::temp_dir().join(format!("sequence-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let mut sequence = Sequence::new(2, 4, directory.clone());
+        sequence.frames_per_second = 2.;
+        let mut times = vec![];
+        let status = sequence
+            .render(
+                &Renderer::new(),
+                4,
+                3,
+                |time| {
+                    times.push(time);
+                    move |_: &_| Color::new(time, 0., 0.)
+                },
+                |_, _| {},
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        assert_eq!(status, RenderStatus::Completed);
+        assert_eq!(times, vec![0.5, 1., 1.5]);
+        for frame in 2..=4 {
+            assert!(sequence.path(frame).exists());
+        }
+        assert!(!sequence.path(1).exists());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn it_stops_at_the_first_cancelled_frame() {
+        let directory = env::temp_dir().join(format!("sequence-cancel-{}", std::process::id()));
+        let sequence = Sequence::new(1, 3, directory.clone());
+        let token = CancellationToken::new();
+        token.cancel();
+        let status = sequence
+            .render(
+                &Renderer::new(),
+                4,
+                3,
+                |_| |_: &_| Color::black(),
+                |_, _| {},
+                &token,
+            )
+            .unwrap();
+        assert_eq!(status, RenderStatus::Cancelled);
+        assert!(!directory.exists());
+    }
+}
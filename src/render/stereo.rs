@@ -0,0 +1,167 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::float::Float;
+use crate::matrix::{Matrix4, Transform};
+use crate::scene::Camera;
+
+// two parallel cameras `interocular` apart whose images line up at the
+// `convergence` distance, objects closer than that pop out of the screen
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StereoRig<T = f64> {
+    pub interocular: T,
+    pub convergence: T,
+}
+
+impl<T: Float> StereoRig<T> {
+    pub fn new(interocular: T, convergence: T) -> Self {
+        Self {
+            interocular,
+            convergence,
+        }
+    }
+
+    // the left and right eye cameras, `camera` being the cyclopean eye
+    pub fn cameras(&self, camera: &Camera<T>) -> (Camera<T>, Camera<T>) {
+        let eye = |offset: T| {
+            let mut eye = *camera;
+            // +x is to the left in camera space
+            let shift = |x: T| Matrix4::translation_matrix(x, T::ZERO, T::ZERO);
+            eye.transform = Transform::with_inverse(
+                shift(-offset) * camera.transform.matrix(),
+                camera.transform.inverse() * shift(offset),
+            );
+            eye.sensor_shift = camera.sensor_shift - offset / self.convergence;
+            eye
+        };
+        let half = self.interocular / T::from_f64(2.);
+        (eye(half), eye(-half))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StereoLayout {
+    SideBySide,
+    OverUnder,
+}
+
+impl StereoLayout {
+    pub fn compose(&self, left: &Canvas, right: &Canvas) -> Canvas {
+        assert!(left.width == right.width && left.height == right.height);
+        let (width, height) = (left.width, left.height);
+        let (mut canvas, (dx, dy)) = match self {
+            StereoLayout::SideBySide => {
+                (Canvas::new(width * 2, height, Color::black()), (width, 0))
+            }
+            StereoLayout::OverUnder => {
+                (Canvas::new(width, height * 2, Color::black()), (0, height))
+            }
+        };
+        for y in 0..height {
+            for x in 0..width {
+                canvas.write_pixel(x, y, *left.read_pixel(x, y));
+                canvas.write_pixel(x + dx, y + dy, *right.read_pixel(x, y));
+            }
+        }
+        canvas
+    }
+}
+
+// red/cyan anaglyph, the red channel comes from the left eye and the green
+// and blue channels from the right eye
+pub fn anaglyph(left: &Canvas, right: &Canvas) -> Canvas {
+    assert!(left.width == right.width && left.height == right.height);
+    let mut canvas = Canvas::new(left.width, left.height, Color::black());
+    for y in 0..left.height {
+        for x in 0..left.width {
+            let (l, r) = (left.read_pixel(x, y), right.read_pixel(x, y));
+            canvas.write_pixel(x, y, Color::new(l.r, r.g, r.b));
+        }
+    }
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{anaglyph, StereoLayout, StereoRig};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+    use crate::float::Float;
+    use crate::matrix::{Matrix4, Transform};
+    use crate::scene::Camera;
+    use crate::tuple::{Point3, Vector3};
+
+    #[test]
+    fn it_separates_the_eyes_by_the_interocular_distance() {
+        let mut camera = Camera::new(101, 101, PI / 2.);
+        camera.transform = Transform::new(Matrix4::view_transform(
+            Point3::point(0., 0., -5.),
+            Point3::origin(),
+            Vector3::vector(0., 1., 0.),
+        ));
+        let (left, right) = StereoRig::new(0.2, 5.).cameras(&camera);
+        let left_ray = left.ray_for_pixel(50, 50).unwrap();
+        let right_ray = right.ray_for_pixel(50, 50).unwrap();
+        // looking toward +z the left of the camera is toward -x
+        assert_abs_diff_eq!(
+            left_ray.origin,
+            Point3::point(-0.1, 0., -5.),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            right_ray.origin,
+            Point3::point(0.1, 0., -5.),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_converges_the_eyes_at_the_convergence_distance() {
+        let camera = Camera::new(101, 101, PI / 2.);
+        let (left, right) = StereoRig::new(0.5, 8.).cameras(&camera);
+        let left_ray = left.ray_for_pixel(50, 50).unwrap();
+        let right_ray = right.ray_for_pixel(50, 50).unwrap();
+        let t = -8. / left_ray.direction.z;
+        assert_abs_diff_eq!(
+            left_ray.position(t),
+            Point3::point(0., 0., -8.),
+            epsilon = 1e-12
+        );
+        let t = -8. / right_ray.direction.z;
+        assert_abs_diff_eq!(
+            right_ray.position(t),
+            Point3::point(0., 0., -8.),
+            epsilon = 1e-12
+        );
+        // no toe in, the eyes still share the same image plane
+        assert_eq!(left.transform.inverse()[(2, 2)], 1.);
+        assert_abs_diff_eq!(
+            left.transform.inverse(),
+            left.transform.matrix().inversed(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn it_composes_side_by_side_and_over_under_layouts() {
+        let left = Canvas::new(3, 2, Color::red());
+        let right = Canvas::new(3, 2, Color::white());
+        let side_by_side = StereoLayout::SideBySide.compose(&left, &right);
+        assert_eq!((side_by_side.width, side_by_side.height), (6, 2));
+        assert_eq!(side_by_side.read_pixel(2, 1), &Color::red());
+        assert_eq!(side_by_side.read_pixel(3, 0), &Color::white());
+        let over_under = StereoLayout::OverUnder.compose(&left, &right);
+        assert_eq!((over_under.width, over_under.height), (3, 4));
+        assert_eq!(over_under.read_pixel(2, 1), &Color::red());
+        assert_eq!(over_under.read_pixel(0, 2), &Color::white());
+    }
+
+    #[test]
+    fn it_merges_the_eyes_into_a_red_cyan_anaglyph() {
+        let left = Canvas::new(2, 2, Color::new(0.8, 0.1, 0.2));
+        let right = Canvas::new(2, 2, Color::new(0.3, 0.6, 0.7));
+        let merged = anaglyph(&left, &right);
+        assert_eq!(merged.read_pixel(1, 1), &Color::new(0.8, 0.6, 0.7));
+    }
+}
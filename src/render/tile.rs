@@ -0,0 +1,72 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Tile {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // split a width x height image in row major order, the tiles on the
+    // right and bottom edges are clipped to the image
+    pub fn grid(width: usize, height: usize, size: usize) -> Vec<Tile> {
+        assert!(size > 0);
+        let mut tiles = vec![];
+        for y in (0..height).step_by(size) {
+            for x in (0..width).step_by(size) {
+                tiles.push(Tile::new(x, y, size.min(width - x), size.min(height - y)));
+            }
+        }
+        tiles
+    }
+
+    pub fn pixel_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)> {
+        let tile = *self;
+        (tile.y..tile.y + tile.height)
+            .flat_map(move |y| (tile.x..tile.x + tile.width).map(move |x| (x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tile;
+
+    #[test]
+    fn it_splits_an_image_into_tiles() {
+        let tiles = Tile::grid(64, 32, 16);
+        assert_eq!(tiles.len(), 8);
+        assert_eq!(tiles[0], Tile::new(0, 0, 16, 16));
+        assert_eq!(tiles[3], Tile::new(48, 0, 16, 16));
+        assert_eq!(tiles[4], Tile::new(0, 16, 16, 16));
+    }
+
+    #[test]
+    fn it_clips_the_tiles_on_the_edges() {
+        let tiles = Tile::grid(20, 10, 16);
+        assert_eq!(
+            tiles,
+            vec![Tile::new(0, 0, 16, 10), Tile::new(16, 0, 4, 10)]
+        );
+        assert_eq!(tiles.iter().map(|t| t.pixel_count()).sum::<usize>(), 200);
+    }
+
+    #[test]
+    fn it_iterates_over_its_pixels_in_row_major_order() {
+        let tile = Tile::new(3, 5, 2, 2);
+        let pixels = tile.pixels().collect::<Vec<_>>();
+        assert_eq!(pixels, vec![(3, 5), (4, 5), (3, 6), (4, 6)]);
+    }
+}
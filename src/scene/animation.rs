@@ -0,0 +1,200 @@
+use crate::color::Color;
+use crate::matrix::Matrix4;
+use crate::tuple::{Point3, Tuple, Vector3};
+
+// how the progress between two keyframes is reshaped before interpolating
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    // hold the value until the next keyframe
+    Step,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1. - (1. - t).powi(3),
+            Easing::EaseInOut => t * t * (3. - 2. * t),
+            Easing::Step => 0.,
+        }
+    }
+}
+
+pub trait Interpolate {
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Tuple {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Point3 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Vector3 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Matrix4 {
+    // blend translation, rotation and scale separately so that objects turn
+    // instead of shrinking through the middle of a rotation
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self.decompose()
+            .interpolate(&other.decompose(), t)
+            .to_matrix()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: f64,
+    pub value: T,
+    // easing of the segment that starts at this keyframe
+    pub easing: Easing,
+}
+
+impl<T> Keyframe<T> {
+    pub fn new(time: f64, value: T, easing: Easing) -> Self {
+        Self {
+            time,
+            value,
+            easing,
+        }
+    }
+}
+
+// a value that changes over time, sorted by keyframe time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate + Clone> Track<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            keyframes: vec![Keyframe::new(0., value, Easing::Linear)],
+        }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    // add a keyframe, replacing the one already at the same time
+    pub fn key(mut self, time: f64, value: T, easing: Easing) -> Self {
+        let keyframe = Keyframe::new(time, value, easing);
+        match self.keyframes.binary_search_by(|k| k.time.total_cmp(&time)) {
+            Ok(i) => self.keyframes[i] = keyframe,
+            Err(i) => self.keyframes.insert(i, keyframe),
+        }
+        self
+    }
+
+    // the value is held before the first and after the last keyframe
+    pub fn at(&self, time: f64) -> T {
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return self.keyframes[0].value.clone();
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1].value.clone();
+        }
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = from
+            .easing
+            .apply((time - from.time) / (to.time - from.time));
+        from.value.interpolate(&to.value, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Easing, Track};
+    use crate::color::Color;
+    use crate::matrix::Matrix4;
+    use crate::tuple::Point3;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn it_eases_between_zero_and_one() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_abs_diff_eq!(easing.apply(0.), 0.);
+            assert_abs_diff_eq!(easing.apply(1.), 1.);
+        }
+        assert_abs_diff_eq!(Easing::EaseIn.apply(0.5), 0.125);
+        assert_abs_diff_eq!(Easing::EaseOut.apply(0.5), 0.875);
+        assert_abs_diff_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_abs_diff_eq!(Easing::Step.apply(0.9), 0.);
+    }
+
+    #[test]
+    fn it_interpolates_between_keyframes() {
+        let track = Track::new(0.)
+            .key(2., 10., Easing::Linear)
+            .key(1., 4., Easing::Linear);
+        assert_abs_diff_eq!(track.at(0.5), 2.);
+        assert_abs_diff_eq!(track.at(1.), 4.);
+        assert_abs_diff_eq!(track.at(1.5), 7.);
+    }
+
+    #[test]
+    fn it_holds_the_value_outside_of_the_keyframes() {
+        let track = Track::new(Color::black()).key(1., Color::white(), Easing::EaseIn);
+        assert_abs_diff_eq!(track.at(-1.), Color::black());
+        assert_abs_diff_eq!(track.at(3.), Color::white());
+    }
+
+    #[test]
+    fn it_applies_the_easing_of_the_segment_start() {
+        let track = Track::new(Point3::point(0., 0., 0.))
+            .key(0., Point3::point(0., 0., 0.), Easing::Step)
+            .key(1., Point3::point(2., 0., 0.), Easing::Linear);
+        assert_abs_diff_eq!(track.at(0.99), Point3::point(0., 0., 0.));
+        assert_abs_diff_eq!(track.at(1.), Point3::point(2., 0., 0.));
+    }
+
+    #[test]
+    fn it_rotates_transforms_instead_of_blending_them() {
+        let track = Track::new(Matrix4::identity()).key(
+            1.,
+            Matrix4::rotation_y_matrix(PI / 2.),
+            Easing::Linear,
+        );
+        assert_abs_diff_eq!(
+            track.at(0.5),
+            Matrix4::rotation_y_matrix(PI / 4.),
+            epsilon = 1e-12
+        );
+    }
+}
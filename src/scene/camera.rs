@@ -0,0 +1,478 @@
+use std::f64::consts::PI;
+
+use crate::float::Float;
+use crate::geo::{Ray, RayPacket};
+use crate::matrix::Transform;
+use crate::render::Sample;
+use crate::tuple::{Point3, Vector3};
+
+// the shape of the out of focus highlights
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Bokeh<T = f64> {
+    Disc,
+    Polygon { blades: usize, rotation: T },
+}
+
+impl<T: Float> Bokeh<T> {
+    // map a point of the unit square onto the aperture of radius 1
+    pub fn sample(&self, u: T, v: T) -> (T, T) {
+        match *self {
+            Bokeh::Disc => concentric_disc(u, v),
+            Bokeh::Polygon { blades, rotation } => {
+                let blades = blades.max(3);
+                // pick one of the triangles fanning from the centre, then
+                // sample it uniformly
+                let scaled = u * T::from_f64(blades as f64);
+                let blade = (scaled.to_f64() as usize).min(blades - 1);
+                let u = scaled - T::from_f64(blade as f64);
+                let corner = |i: usize| {
+                    let angle = rotation + T::from_f64(2. * PI * i as f64 / blades as f64);
+                    (angle.cos(), angle.sin())
+                };
+                let (a, b) = (corner(blade), corner(blade + 1));
+                let (su, w) = (u.sqrt(), T::ONE - v);
+                (su * (w * a.0 + v * b.0), su * (w * a.1 + v * b.1))
+            }
+        }
+    }
+}
+
+// Shirley's concentric mapping keeps the strata of the sample pattern
+fn concentric_disc<T: Float>(u: T, v: T) -> (T, T) {
+    let two = T::from_f64(2.);
+    let (a, b) = (two * u - T::ONE, two * v - T::ONE);
+    if a == T::ZERO && b == T::ZERO {
+        return (T::ZERO, T::ZERO);
+    }
+    let quarter = T::from_f64(PI / 4.);
+    let (radius, theta) = if a.abs() > b.abs() {
+        (a, quarter * (b / a))
+    } else {
+        (b, T::from_f64(PI / 2.) - quarter * (a / b))
+    };
+    (radius * theta.cos(), radius * theta.sin())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    // the distance from the image centre is proportional to the angle
+    Equidistant,
+    // preserves the solid angle, areas keep their relative size
+    Equisolid,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection<T = f64> {
+    Perspective,
+    // `size` is the extent in world units of the longest side of the image
+    Orthographic { size: T },
+    // the image circle fits the shortest side and spans `field_of_view`
+    Fisheye(FisheyeMapping),
+    // full 360 by 180 degrees latitude/longitude panorama
+    Equirectangular,
+}
+
+// a thin lens camera looking toward -z in its own coordinate system, with
+// an aperture of 0 it behaves like a pinhole and everything is in focus.
+// The fisheye and panoramic projections are always pinholes. The samples
+// are double precision, the rays are traced in `T`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera<T = f64> {
+    pub hsize: usize,
+    pub vsize: usize,
+    pub field_of_view: T,
+    pub transform: Transform<T>,
+    pub projection: Projection<T>,
+    // horizontal offset of the image plane, one unit in front of the camera,
+    // used to converge stereo pairs without toeing them in
+    pub sensor_shift: T,
+    pub aperture: T,
+    pub focal_distance: T,
+    pub bokeh: Bokeh<T>,
+    pub shutter_open: T,
+    pub shutter_close: T,
+}
+
+impl<T: Float> Camera<T> {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: T) -> Self {
+        Self {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Transform::identity(),
+            projection: Projection::Perspective,
+            sensor_shift: T::ZERO,
+            aperture: T::ZERO,
+            focal_distance: T::ONE,
+            bokeh: Bokeh::Disc,
+            shutter_open: T::ZERO,
+            shutter_close: T::ZERO,
+        }
+    }
+
+    pub fn orthographic(hsize: usize, vsize: usize, size: T) -> Self {
+        let mut camera = Self::new(hsize, vsize, T::ZERO);
+        camera.projection = Projection::Orthographic { size };
+        camera
+    }
+
+    // half width and half height of the image plane, one unit in front of
+    // the camera for the perspective projection
+    fn half_extents(&self) -> (T, T) {
+        let two = T::from_f64(2.);
+        let half_view = match self.projection {
+            Projection::Orthographic { size } => size / two,
+            _ => (self.field_of_view / two).tan(),
+        };
+        let aspect = T::from_f64(self.hsize as f64 / self.vsize as f64);
+        if aspect >= T::ONE {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        }
+    }
+
+    pub fn pixel_size(&self) -> T {
+        self.half_extents().0 * T::from_f64(2.) / T::from_f64(self.hsize as f64)
+    }
+
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Option<Ray<T>> {
+        self.ray_for_sample(&Sample::new(x as f64 + 0.5, y as f64 + 0.5))
+    }
+
+    // N neighbouring pixels of a row starting at (x, y), the primary rays
+    // are coherent enough to be traced together. The mask tells which lanes
+    // hold a ray: the lanes past the end of the row and outside of a fisheye
+    // circle are inactive and repeat an active ray. None when no lane is
+    // active.
+    pub fn packet_for_pixels<const N: usize>(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> Option<(RayPacket<N, T>, [bool; N])> {
+        let rays: [Option<Ray<T>>; N] = std::array::from_fn(|i| {
+            (x + i < self.hsize)
+                .then(|| self.ray_for_pixel(x + i, y))
+                .flatten()
+        });
+        let fill = rays.iter().flatten().next().copied()?;
+        Some((
+            RayPacket::new(rays.map(|ray| ray.unwrap_or(fill))),
+            rays.map(|ray| ray.is_some()),
+        ))
+    }
+
+    // None when the sample falls outside of the image circle of a fisheye
+    pub fn ray_for_sample(&self, sample: &Sample) -> Option<Ray<T>> {
+        let (zero, one) = (T::ZERO, T::ONE);
+        let (origin, direction) = match self.projection {
+            Projection::Perspective => {
+                let (x, y) = self.image_plane(sample);
+                self.thin_lens(Point3::origin(), Vector3::vector(x, y, -one), sample)
+            }
+            Projection::Orthographic { .. } => {
+                let (x, y) = self.image_plane(sample);
+                self.thin_lens(
+                    Point3::point(x, y, zero),
+                    Vector3::vector(zero, zero, -one),
+                    sample,
+                )
+            }
+            Projection::Fisheye(mapping) => {
+                (Point3::origin(), self.fisheye_direction(mapping, sample)?)
+            }
+            Projection::Equirectangular => {
+                let longitude = T::from_f64((sample.x / self.hsize as f64 - 0.5) * 2. * PI);
+                let latitude = T::from_f64((0.5 - sample.y / self.vsize as f64) * PI);
+                let direction = Vector3::vector(
+                    -longitude.sin() * latitude.cos(),
+                    latitude.sin(),
+                    -longitude.cos() * latitude.cos(),
+                );
+                (Point3::origin(), direction)
+            }
+        };
+
+        let inverse = self.transform.inverse();
+        let time =
+            self.shutter_open + (self.shutter_close - self.shutter_open) * T::from_f64(sample.time);
+        Some(Ray::with_time(
+            inverse * origin,
+            (inverse * direction).normalized(),
+            time,
+        ))
+    }
+
+    // position on the image plane in camera space, the camera looks toward
+    // -z, so +x is to the left
+    fn image_plane(&self, sample: &Sample) -> (T, T) {
+        let (half_width, half_height) = self.half_extents();
+        let pixel_size = self.pixel_size();
+        let shift = match self.projection {
+            Projection::Perspective => self.sensor_shift,
+            _ => T::ZERO,
+        };
+        (
+            half_width - T::from_f64(sample.x) * pixel_size + shift,
+            half_height - T::from_f64(sample.y) * pixel_size,
+        )
+    }
+
+    // move the origin of the ray on the lens while keeping it pointed at the
+    // point where the pinhole ray crosses the focal plane
+    fn thin_lens(
+        &self,
+        origin: Point3<T>,
+        direction: Vector3<T>,
+        sample: &Sample,
+    ) -> (Point3<T>, Vector3<T>) {
+        if self.aperture <= T::ZERO {
+            return (origin, direction);
+        }
+        let focus = origin + direction * (self.focal_distance / -direction.z);
+        let (lens_x, lens_y) = self
+            .bokeh
+            .sample(T::from_f64(sample.lens_u), T::from_f64(sample.lens_v));
+        let offset = Vector3::vector(lens_x * self.aperture, lens_y * self.aperture, T::ZERO);
+        let origin = origin + offset;
+        (origin, focus - origin)
+    }
+
+    fn fisheye_direction(&self, mapping: FisheyeMapping, sample: &Sample) -> Option<Vector3<T>> {
+        let radius = self.hsize.min(self.vsize) as f64 / 2.;
+        let nx = T::from_f64((sample.x - self.hsize as f64 / 2.) / radius);
+        let ny = T::from_f64((sample.y - self.vsize as f64 / 2.) / radius);
+        let r = (nx * nx + ny * ny).sqrt();
+        if r > T::ONE {
+            return None;
+        }
+        let (two, four) = (T::from_f64(2.), T::from_f64(4.));
+        let theta = match mapping {
+            FisheyeMapping::Equidistant => r * self.field_of_view / two,
+            FisheyeMapping::Equisolid => two * (r * (self.field_of_view / four).sin()).asin(),
+        };
+        if r == T::ZERO {
+            return Some(Vector3::vector(T::ZERO, T::ZERO, -T::ONE));
+        }
+        // image right is -x and image down is -y in camera space
+        Some(Vector3::vector(
+            -theta.sin() * nx / r,
+            -theta.sin() * ny / r,
+            -theta.cos(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{Bokeh, Camera, FisheyeMapping, Projection};
+    use crate::matrix::{Matrix4, Transform};
+    use crate::render::Sample;
+    use crate::tuple::{Point3, Vector3};
+
+    #[test]
+    fn it_computes_the_pixel_size_for_a_horizontal_canvas() {
+        let camera = Camera::new(200, 125, PI / 2.);
+        assert_abs_diff_eq!(camera.pixel_size(), 0.01, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_computes_the_pixel_size_for_a_vertical_canvas() {
+        let camera = Camera::new(125, 200, PI / 2.);
+        assert_abs_diff_eq!(camera.pixel_size(), 0.01, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_casts_a_ray_through_the_center_of_the_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.);
+        let ray = camera.ray_for_pixel(100, 50).unwrap();
+        assert_abs_diff_eq!(ray.origin, Point3::point(0., 0., 0.));
+        assert_abs_diff_eq!(ray.direction, Vector3::vector(0., 0., -1.), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_casts_a_ray_through_a_corner_of_the_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.);
+        let ray = camera.ray_for_pixel(0, 0).unwrap();
+        assert_abs_diff_eq!(ray.origin, Point3::point(0., 0., 0.));
+        assert_abs_diff_eq!(
+            ray.direction,
+            Vector3::vector(0.66519, 0.33259, -0.66851),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn it_casts_a_ray_when_the_camera_is_transformed() {
+        let mut camera = Camera::new(201, 101, PI / 2.);
+        camera.transform = Transform::new(
+            Matrix4::rotation_y_matrix(PI / 4.) * Matrix4::translation_matrix(0., -2., 5.),
+        );
+        let ray = camera.ray_for_pixel(100, 50).unwrap();
+        assert_abs_diff_eq!(ray.origin, Point3::point(0., 2., -5.), epsilon = 1e-12);
+        let v = f64::sqrt(2.) / 2.;
+        assert_abs_diff_eq!(ray.direction, Vector3::vector(v, 0., -v), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_focuses_every_lens_sample_on_the_focal_plane() {
+        let mut camera = Camera::new(101, 101, PI / 2.);
+        camera.aperture = 0.5;
+        camera.focal_distance = 4.;
+        let on_focal_plane = |sample: Sample| {
+            let ray = camera.ray_for_sample(&sample).unwrap();
+            ray.position(-4. / ray.direction.z)
+        };
+        let center = on_focal_plane(Sample::with_lens(30.5, 70.5, 0.5, 0.5));
+        for (u, v) in [(0., 0.), (0.9, 0.1), (0.25, 0.8)] {
+            let ray = camera
+                .ray_for_sample(&Sample::with_lens(30.5, 70.5, u, v))
+                .unwrap();
+            assert_ne!(ray.origin, Point3::origin());
+            assert_abs_diff_eq!(
+                on_focal_plane(Sample::with_lens(30.5, 70.5, u, v)),
+                center,
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn it_keeps_the_lens_samples_inside_the_aperture() {
+        let hexagon = Bokeh::Polygon {
+            blades: 6,
+            rotation: 0.,
+        };
+        // the inscribed radius of a regular hexagon
+        let apothem = (PI / 6.).cos();
+        for i in 0..20 {
+            for j in 0..20 {
+                let (u, v) = (i as f64 / 20., j as f64 / 20.);
+                let (x, y) = Bokeh::Disc.sample(u, v);
+                assert!(x * x + y * y <= 1. + 1e-12);
+                let (x, y) = hexagon.sample(u, v);
+                let angle = y.atan2(x).rem_euclid(PI / 3.) - PI / 6.;
+                assert!((x * x + y * y).sqrt() * angle.cos() <= apothem + 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn it_spreads_the_ray_times_over_the_shutter_interval() {
+        let mut camera = Camera::new(11, 11, PI / 2.);
+        camera.shutter_open = 2.;
+        camera.shutter_close = 2.5;
+        let mut sample = Sample::new(5.5, 5.5);
+        assert_abs_diff_eq!(camera.ray_for_sample(&sample).unwrap().time, 2.);
+        sample.time = 0.5;
+        assert_abs_diff_eq!(camera.ray_for_sample(&sample).unwrap().time, 2.25);
+    }
+
+    #[test]
+    fn it_casts_parallel_rays_with_an_orthographic_projection() {
+        let camera = Camera::orthographic(200, 100, 4.);
+        assert_abs_diff_eq!(camera.pixel_size(), 0.02);
+        let center = camera.ray_for_pixel(100, 50).unwrap();
+        let corner = camera.ray_for_sample(&Sample::new(0., 0.)).unwrap();
+        assert_abs_diff_eq!(
+            center.origin,
+            Point3::point(-0.01, -0.01, 0.),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(corner.origin, Point3::point(2., 1., 0.));
+        assert_abs_diff_eq!(center.direction, Vector3::vector(0., 0., -1.));
+        assert_abs_diff_eq!(corner.direction, Vector3::vector(0., 0., -1.));
+    }
+
+    #[test]
+    fn it_maps_the_image_circle_of_a_fisheye() {
+        for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let mut camera = Camera::new(200, 100, PI);
+            camera.projection = Projection::Fisheye(mapping);
+            let center = camera.ray_for_sample(&Sample::new(100., 50.)).unwrap();
+            assert_abs_diff_eq!(center.direction, Vector3::vector(0., 0., -1.));
+            // the rim of a 180 degrees fisheye looks sideways
+            let rim = camera.ray_for_sample(&Sample::new(150., 50.)).unwrap();
+            assert_abs_diff_eq!(rim.direction, Vector3::vector(-1., 0., 0.), epsilon = 1e-12);
+            let top = camera.ray_for_sample(&Sample::new(100., 0.)).unwrap();
+            assert_abs_diff_eq!(top.direction, Vector3::vector(0., 1., 0.), epsilon = 1e-12);
+            assert_eq!(camera.ray_for_sample(&Sample::new(10., 10.)), None);
+        }
+    }
+
+    #[test]
+    fn it_traces_neighbouring_pixels_as_a_packet() {
+        let mut camera = Camera::new(201, 101, PI / 2.);
+        camera.transform =
+            Transform::new(Matrix4::rotation_y_matrix(PI / 4.).translate(0., -2., 5.));
+        let (packet, mask) = camera.packet_for_pixels::<4>(98, 50).unwrap();
+        assert_eq!(mask, [true; 4]);
+        for (lane, ray) in packet.rays().iter().enumerate() {
+            assert_eq!(*ray, camera.ray_for_pixel(98 + lane, 50).unwrap());
+        }
+        // the left of the row falls outside of the fisheye circle
+        camera.projection = Projection::Fisheye(FisheyeMapping::Equidistant);
+        let (packet, mask) = camera.packet_for_pixels::<8>(46, 50).unwrap();
+        assert_eq!(mask, [false, false, false, false, true, true, true, true]);
+        assert_eq!(packet.ray(7), camera.ray_for_pixel(53, 50).unwrap());
+        assert_eq!(camera.packet_for_pixels::<8>(0, 0), None);
+    }
+
+    #[test]
+    fn it_masks_the_lanes_past_the_end_of_the_row() {
+        // 201 is not a multiple of 8, the last packet holds one pixel
+        let camera = Camera::new(201, 101, PI / 2.);
+        let (packet, mask) = camera.packet_for_pixels::<8>(200, 50).unwrap();
+        assert_eq!(
+            mask,
+            [true, false, false, false, false, false, false, false]
+        );
+        assert_eq!(packet.ray(0), camera.ray_for_pixel(200, 50).unwrap());
+        let active = (0..201)
+            .step_by(8)
+            .flat_map(|x| camera.packet_for_pixels::<8>(x, 50).unwrap().1)
+            .filter(|active| *active)
+            .count();
+        assert_eq!(active, 201);
+        assert_eq!(camera.packet_for_pixels::<8>(201, 50), None);
+    }
+
+    #[test]
+    fn it_distinguishes_the_fisheye_mappings() {
+        let mut camera = Camera::new(100, 100, PI);
+        camera.projection = Projection::Fisheye(FisheyeMapping::Equidistant);
+        let sample = Sample::new(75., 50.);
+        let equidistant = camera.ray_for_sample(&sample).unwrap().direction;
+        assert_abs_diff_eq!(equidistant.z, -(PI / 4.).cos(), epsilon = 1e-12);
+        camera.projection = Projection::Fisheye(FisheyeMapping::Equisolid);
+        let equisolid = camera.ray_for_sample(&sample).unwrap().direction;
+        let theta = 2. * (0.5 * (PI / 4.).sin()).asin();
+        assert_abs_diff_eq!(equisolid.z, -theta.cos(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn it_covers_the_whole_sphere_with_an_equirectangular_projection() {
+        let mut camera = Camera::new(360, 180, 0.);
+        camera.projection = Projection::Equirectangular;
+        let ray = |x: f64, y: f64| camera.ray_for_sample(&Sample::new(x, y)).unwrap().direction;
+        assert_abs_diff_eq!(
+            ray(180., 90.),
+            Vector3::vector(0., 0., -1.),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            ray(270., 90.),
+            Vector3::vector(-1., 0., 0.),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(ray(0., 90.), Vector3::vector(0., 0., 1.), epsilon = 1e-12);
+        assert_abs_diff_eq!(ray(180., 0.), Vector3::vector(0., 1., 0.), epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            ray(42., 180.),
+            Vector3::vector(0., -1., 0.),
+            epsilon = 1e-12
+        );
+    }
+}
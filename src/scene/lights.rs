@@ -1,13 +1,13 @@
-use crate::{color::Color, tuple::Point3};
+use crate::{color::Color, float::Float, tuple::Point3};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct PointLight {
-    pub position: Point3,
-    pub intensity: Color,
+pub struct PointLight<T = f64> {
+    pub position: Point3<T>,
+    pub intensity: Color<T>,
 }
 
-impl PointLight {
-    pub fn new(position: Point3, intensity: Color) -> Self {
+impl<T: Float> PointLight<T> {
+    pub fn new(position: Point3<T>, intensity: Color<T>) -> Self {
         Self { position, intensity }
     }
 }
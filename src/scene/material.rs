@@ -1,21 +1,34 @@
 use crate::{
     color::Color,
-    tuple::{Point3, Vector3},
+    float::Float,
+    tuple::{Normal3, Point3, Vector3},
 };
 
 use super::PointLight;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Material {
-    pub color: Color,
-    pub ambient: f64,
-    pub diffuse: f64,
-    pub specular: f64,
-    pub shininess: f64,
+pub struct Material<T = f64> {
+    pub color: Color<T>,
+    pub ambient: T,
+    pub diffuse: T,
+    pub specular: T,
+    pub shininess: T,
 }
 
-impl Material {
-    pub fn new(color: Color, ambient: f64, diffuse: f64, specular: f64, shininess: f64) -> Self {
+impl<T: Float> Default for Material<T> {
+    fn default() -> Self {
+        Self::new(
+            Color::white(),
+            T::from_f64(0.1),
+            T::from_f64(0.9),
+            T::from_f64(0.9),
+            T::from_f64(200.),
+        )
+    }
+}
+
+impl<T: Float> Material<T> {
+    pub fn new(color: Color<T>, ambient: T, diffuse: T, specular: T, shininess: T) -> Self {
         Self {
             color,
             ambient,
@@ -25,17 +38,13 @@ impl Material {
         }
     }
 
-    pub fn default() -> Self {
-        Self::new(Color::new(1., 1., 1.), 0.1, 0.9, 0.9, 200.)
-    }
-
     pub fn lighting(
         &self,
-        light: PointLight,
-        position: Point3,
-        eye_vector: Vector3,
-        normal_vector: Vector3,
-    ) -> Color {
+        light: PointLight<T>,
+        position: Point3<T>,
+        eye_vector: Vector3<T>,
+        normal_vector: Normal3<T>,
+    ) -> Color<T> {
         // combine the surface color with the light's color intensity
         let effective_color = self.color * light.intensity;
 
@@ -47,9 +56,9 @@ impl Material {
 
         // light_dot_normal is the cosine of the angle between light and normal vectors
         // if negative then the light is on the other side of the surface
-        let light_dot_normal = light_vector.dot(normal_vector);
+        let light_dot_normal = normal_vector.dot(light_vector);
 
-        let (diffuse_contrib, specular_contrib) = if light_dot_normal < 0. {
+        let (diffuse_contrib, specular_contrib) = if light_dot_normal < T::ZERO {
             (Color::black(), Color::black())
         } else {
             // calculate the diffuse contribution
@@ -61,7 +70,7 @@ impl Material {
             // if negative then the light is reflecting away from the eye
             let reflect_dot_eye = reflected_vector.dot(eye_vector);
 
-            let specular_contrib = if reflect_dot_eye < 0. {
+            let specular_contrib = if reflect_dot_eye < T::ZERO {
                 Color::black()
             } else {
                 // calculate the specular contribution
@@ -80,7 +89,7 @@ mod tests {
     use super::Material;
     use crate::color::Color;
     use crate::scene::PointLight;
-    use crate::tuple::{Point3, Vector3};
+    use crate::tuple::{Normal3, Point3, Vector3};
 
     #[test]
     fn it_has_default() {
@@ -93,7 +102,7 @@ mod tests {
         let material = Material::default();
         let position = Point3::point(0., 0., 0.);
         let eye_vector = Vector3::vector(0., 0., -1.);
-        let normal_vector = Vector3::vector(0., 0., -1.);
+        let normal_vector = Normal3::normal(0., 0., -1.);
         let light = PointLight::new(Point3::point(0., 0., -10.), Color::new(1., 1., 1.));
         let result = material.lighting(light, position, eye_vector, normal_vector);
         assert_abs_diff_eq!(result, Color::new(1.9, 1.9, 1.9));
@@ -105,7 +114,7 @@ mod tests {
         let material = Material::default();
         let position = Point3::point(0., 0., 0.);
         let eye_vector = Vector3::vector(0., f64::sqrt(2.) / 2., f64::sqrt(2.) / 2.);
-        let normal_vector = Vector3::vector(0., 0., -1.);
+        let normal_vector = Normal3::normal(0., 0., -1.);
         let light = PointLight::new(Point3::point(0., 0., -10.), Color::new(1., 1., 1.));
         let result = material.lighting(light, position, eye_vector, normal_vector);
         assert_abs_diff_eq!(result, Color::new(1., 1., 1.));
@@ -117,7 +126,7 @@ mod tests {
         let material = Material::default();
         let position = Point3::point(0., 0., 0.);
         let eye_vector = Vector3::vector(0., 0., -1.);
-        let normal_vector = Vector3::vector(0., 0., -1.);
+        let normal_vector = Normal3::normal(0., 0., -1.);
         let light = PointLight::new(Point3::point(0., 10., -10.), Color::new(1., 1., 1.));
         let result = material.lighting(light, position, eye_vector, normal_vector);
         let expected_value = 0.1 + 0.9 * f64::sqrt(2.) / 2. + 0.;
@@ -132,7 +141,7 @@ mod tests {
         let material = Material::default();
         let position = Point3::point(0., 0., 0.);
         let eye_vector = Vector3::vector(0., -f64::sqrt(2.) / 2., -f64::sqrt(2.) / 2.);
-        let normal_vector = Vector3::vector(0., 0., -1.);
+        let normal_vector = Normal3::normal(0., 0., -1.);
         let light = PointLight::new(Point3::point(0., 10., -10.), Color::new(1., 1., 1.));
         let result = material.lighting(light, position, eye_vector, normal_vector);
         let expected_value = 0.1 + 0.9 * f64::sqrt(2.) / 2. + 0.9;
@@ -147,7 +156,7 @@ mod tests {
         let material = Material::default();
         let position = Point3::point(0., 0., 0.);
         let eye_vector = Vector3::vector(0., 0., -1.);
-        let normal_vector = Vector3::vector(0., 0., -1.);
+        let normal_vector = Normal3::normal(0., 0., -1.);
         let light = PointLight::new(Point3::point(0., 0., 10.), Color::new(1., 1., 1.));
         let result = material.lighting(light, position, eye_vector, normal_vector);
         assert_abs_diff_eq!(result, Color::new(0.1, 0.1, 0.1));
@@ -1,6 +1,9 @@
+mod animation;
+mod camera;
 mod lights;
 mod material;
 
+pub use animation::{Easing, Interpolate, Keyframe, Track};
+pub use camera::{Bokeh, Camera, FisheyeMapping, Projection};
 pub use lights::PointLight;
 pub use material::Material;
-
@@ -0,0 +1,125 @@
+#[cfg(not(target_arch = "x86_64"))]
+use super::scalar;
+
+// a tuple fits in a single SSE register, AVX would bring nothing
+#[inline]
+pub fn add4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse::add4(a, b)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::add4(a, b)
+}
+
+#[inline]
+pub fn sub4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse::sub4(a, b)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::sub4(a, b)
+}
+
+#[inline]
+pub fn mul4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse::mul4(a, b)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::mul4(a, b)
+}
+
+#[inline]
+pub fn scale4(a: [f32; 4], factor: f32) -> [f32; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse::scale4(a, factor)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::scale4(a, factor)
+}
+
+#[inline]
+pub fn mul_matrix4_tuple(m: &[[f32; 4]; 4], v: [f32; 4]) -> [f32; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse::mul_matrix4_tuple(m, v)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::mul_matrix4_tuple(m, v)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse {
+    use std::arch::x86_64::*;
+
+    #[inline]
+    unsafe fn load(v: &[f32; 4]) -> __m128 {
+        _mm_loadu_ps(v.as_ptr())
+    }
+
+    #[inline]
+    unsafe fn store(v: __m128) -> [f32; 4] {
+        let mut res = [0.; 4];
+        _mm_storeu_ps(res.as_mut_ptr(), v);
+        res
+    }
+
+    #[inline]
+    pub unsafe fn add4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        store(_mm_add_ps(load(&a), load(&b)))
+    }
+
+    #[inline]
+    pub unsafe fn sub4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        store(_mm_sub_ps(load(&a), load(&b)))
+    }
+
+    #[inline]
+    pub unsafe fn mul4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        store(_mm_mul_ps(load(&a), load(&b)))
+    }
+
+    #[inline]
+    pub unsafe fn scale4(a: [f32; 4], factor: f32) -> [f32; 4] {
+        store(_mm_mul_ps(load(&a), _mm_set1_ps(factor)))
+    }
+
+    #[inline]
+    pub unsafe fn mul_matrix4_tuple(m: &[[f32; 4]; 4], v: [f32; 4]) -> [f32; 4] {
+        let v = load(&v);
+        let [p0, p1, p2, p3] = m.map(|row| _mm_mul_ps(load(&row), v));
+        // transposed so that lane i sums the products of row i, without the
+        // horizontal adds of SSE3
+        let s01 = _mm_add_ps(_mm_unpacklo_ps(p0, p1), _mm_unpackhi_ps(p0, p1));
+        let s23 = _mm_add_ps(_mm_unpacklo_ps(p2, p3), _mm_unpackhi_ps(p2, p3));
+        store(_mm_add_ps(_mm_movelh_ps(s01, s23), _mm_movehl_ps(s23, s01)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::scalar;
+
+    #[test]
+    fn it_matches_the_scalar_kernels() {
+        let m = [
+            [1., 2., 3., 4.],
+            [5., 6., 7., 8.],
+            [9., 8., 7., 6.],
+            [5., 4., 3., 2.],
+        ];
+        let (a, b) = ([1.5, -2., 0.25, 1.], [3., 4., -8., 0.]);
+        assert_eq!(super::add4(a, b), scalar::add4(a, b));
+        assert_eq!(super::sub4(a, b), scalar::sub4(a, b));
+        assert_eq!(super::mul4(a, b), scalar::mul4(a, b));
+        assert_eq!(super::scale4(a, -3.), scalar::scale4(a, -3.));
+        assert_eq!(
+            super::mul_matrix4_tuple(&m, [1., 2., 3., 1.]),
+            [18., 46., 52., 24.]
+        );
+    }
+}
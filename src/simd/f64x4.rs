@@ -0,0 +1,135 @@
+#[cfg(not(target_arch = "x86_64"))]
+use super::scalar;
+
+// SSE2, which every x86_64 cpu has, a runtime check for AVX would keep the
+// kernels from being inlined
+#[inline]
+pub fn add4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse2::add4(a, b)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::add4(a, b)
+}
+
+#[inline]
+pub fn sub4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse2::sub4(a, b)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::sub4(a, b)
+}
+
+#[inline]
+pub fn mul4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse2::mul4(a, b)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::mul4(a, b)
+}
+
+#[inline]
+pub fn scale4(a: [f64; 4], factor: f64) -> [f64; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse2::scale4(a, factor)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::scale4(a, factor)
+}
+
+#[inline]
+pub fn mul_matrix4_tuple(m: &[[f64; 4]; 4], v: [f64; 4]) -> [f64; 4] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        sse2::mul_matrix4_tuple(m, v)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    scalar::mul_matrix4_tuple(m, v)
+}
+
+// two registers per tuple, xy and zw
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use std::arch::x86_64::*;
+
+    #[inline]
+    unsafe fn load(v: &[f64; 4]) -> (__m128d, __m128d) {
+        (_mm_loadu_pd(v.as_ptr()), _mm_loadu_pd(v.as_ptr().add(2)))
+    }
+
+    #[inline]
+    unsafe fn store((xy, zw): (__m128d, __m128d)) -> [f64; 4] {
+        let mut res = [0.; 4];
+        _mm_storeu_pd(res.as_mut_ptr(), xy);
+        _mm_storeu_pd(res.as_mut_ptr().add(2), zw);
+        res
+    }
+
+    #[inline]
+    pub unsafe fn add4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+        let (a, b) = (load(&a), load(&b));
+        store((_mm_add_pd(a.0, b.0), _mm_add_pd(a.1, b.1)))
+    }
+
+    #[inline]
+    pub unsafe fn sub4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+        let (a, b) = (load(&a), load(&b));
+        store((_mm_sub_pd(a.0, b.0), _mm_sub_pd(a.1, b.1)))
+    }
+
+    #[inline]
+    pub unsafe fn mul4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+        let (a, b) = (load(&a), load(&b));
+        store((_mm_mul_pd(a.0, b.0), _mm_mul_pd(a.1, b.1)))
+    }
+
+    #[inline]
+    pub unsafe fn scale4(a: [f64; 4], factor: f64) -> [f64; 4] {
+        let (a, factor) = (load(&a), _mm_set1_pd(factor));
+        store((_mm_mul_pd(a.0, factor), _mm_mul_pd(a.1, factor)))
+    }
+
+    #[inline]
+    pub unsafe fn mul_matrix4_tuple(m: &[[f64; 4]; 4], v: [f64; 4]) -> [f64; 4] {
+        let v = load(&v);
+        // the two halves of each row's dot product
+        let [p0, p1, p2, p3] = m.map(|row| {
+            let row = load(&row);
+            _mm_add_pd(_mm_mul_pd(row.0, v.0), _mm_mul_pd(row.1, v.1))
+        });
+        store((
+            _mm_add_pd(_mm_unpacklo_pd(p0, p1), _mm_unpackhi_pd(p0, p1)),
+            _mm_add_pd(_mm_unpacklo_pd(p2, p3), _mm_unpackhi_pd(p2, p3)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::scalar;
+
+    #[test]
+    fn it_matches_the_scalar_kernels() {
+        let m = [
+            [1., 2., 3., 4.],
+            [5., 6., 7., 8.],
+            [9., 8., 7., 6.],
+            [5., 4., 3., 2.],
+        ];
+        let (a, b) = ([1.5, -2., 0.25, 1.], [3., 4., -8., 0.]);
+        assert_eq!(super::add4(a, b), scalar::add4(a, b));
+        assert_eq!(super::sub4(a, b), scalar::sub4(a, b));
+        assert_eq!(super::mul4(a, b), scalar::mul4(a, b));
+        assert_eq!(super::scale4(a, -3.), scalar::scale4(a, -3.));
+        assert_eq!(
+            super::mul_matrix4_tuple(&m, [1., 2., 3., 1.]),
+            [18., 46., 52., 24.]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+// 4-wide kernels behind the tuple arithmetic and the matrix times tuple
+// products. On x86_64 they use SSE2, which every x86_64 cpu has, the other
+// targets use the scalar loops. Matrix products stay plain loops, the
+// compiler vectorizes them as well as the intrinsics did.
+mod f32x4;
+mod f64x4;
+mod scalar;
+
+// the kernels of f32 and f64, required by Float. Public in this private
+// module so that Float can name it without it showing in the api.
+pub trait Kernels: Sized {
+    fn add4(a: [Self; 4], b: [Self; 4]) -> [Self; 4];
+    fn sub4(a: [Self; 4], b: [Self; 4]) -> [Self; 4];
+    fn mul4(a: [Self; 4], b: [Self; 4]) -> [Self; 4];
+    fn scale4(a: [Self; 4], factor: Self) -> [Self; 4];
+    fn mul_matrix4_tuple(m: &[[Self; 4]; 4], v: [Self; 4]) -> [Self; 4];
+}
+
+macro_rules! impl_kernels {
+    ($type:ident, $kernels:ident) => {
+        impl Kernels for $type {
+            #[inline]
+            fn add4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+                $kernels::add4(a, b)
+            }
+
+            #[inline]
+            fn sub4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+                $kernels::sub4(a, b)
+            }
+
+            #[inline]
+            fn mul4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+                $kernels::mul4(a, b)
+            }
+
+            #[inline]
+            fn scale4(a: [Self; 4], factor: Self) -> [Self; 4] {
+                $kernels::scale4(a, factor)
+            }
+
+            #[inline]
+            fn mul_matrix4_tuple(m: &[[Self; 4]; 4], v: [Self; 4]) -> [Self; 4] {
+                $kernels::mul_matrix4_tuple(m, v)
+            }
+        }
+    };
+}
+
+impl_kernels!(f32, f32x4);
+impl_kernels!(f64, f64x4);
@@ -0,0 +1,27 @@
+use crate::float::Float;
+
+#[inline]
+pub fn add4<T: Float>(a: [T; 4], b: [T; 4]) -> [T; 4] {
+    [0, 1, 2, 3].map(|i| a[i] + b[i])
+}
+
+#[inline]
+pub fn sub4<T: Float>(a: [T; 4], b: [T; 4]) -> [T; 4] {
+    [0, 1, 2, 3].map(|i| a[i] - b[i])
+}
+
+#[inline]
+pub fn mul4<T: Float>(a: [T; 4], b: [T; 4]) -> [T; 4] {
+    [0, 1, 2, 3].map(|i| a[i] * b[i])
+}
+
+#[inline]
+pub fn scale4<T: Float>(a: [T; 4], factor: T) -> [T; 4] {
+    a.map(|value| value * factor)
+}
+
+// row major, the tuple is a column
+#[inline]
+pub fn mul_matrix4_tuple<T: Float>(m: &[[T; 4]; 4], v: [T; 4]) -> [T; 4] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2] + row[3] * v[3])
+}
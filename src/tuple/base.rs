@@ -1,57 +1,68 @@
+use crate::float::Float;
 use approx::AbsDiffEq;
 use std::fmt;
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Tuple {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
-    pub w: f64,
+pub struct Tuple<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
 }
 
-impl Tuple {
-    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+impl<T: Float> Tuple<T> {
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
         Self { x, y, z, w }
     }
 
+    pub fn point(x: T, y: T, z: T) -> Self {
+        Self::new(x, y, z, T::ONE)
+    }
+
+    pub fn vector(x: T, y: T, z: T) -> Self {
+        Self::new(x, y, z, T::ZERO)
+    }
+
     pub fn is_point(&self) -> bool {
-        self.w == 1.0
+        self.w == T::ONE
     }
 
     pub fn is_vector(&self) -> bool {
-        self.w == 0.0
+        self.w == T::ZERO
+    }
+}
+
+impl<T: Float> From<[T; 4]> for Tuple<T> {
+    fn from([x, y, z, w]: [T; 4]) -> Self {
+        Self::new(x, y, z, w)
+    }
+}
+
+impl<T: Float> From<Tuple<T>> for [T; 4] {
+    fn from(tuple: Tuple<T>) -> Self {
+        [tuple.x, tuple.y, tuple.z, tuple.w]
     }
 }
 
-impl Add for Tuple {
+impl<T: Float> Add for Tuple<T> {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-            z: self.z + other.z,
-            w: self.w + other.w,
-        }
+        Self::from(T::add4(self.into(), other.into()))
     }
 }
 
-impl Sub for Tuple {
+impl<T: Float> Sub for Tuple<T> {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-            w: self.w - other.w,
-        }
+        Self::from(T::sub4(self.into(), other.into()))
     }
 }
 
-impl Neg for Tuple {
+impl<T: Float> Neg for Tuple<T> {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
@@ -64,36 +75,26 @@ impl Neg for Tuple {
     }
 }
 
-impl Mul<f64> for Tuple {
+impl<T: Float> Mul<T> for Tuple<T> {
     type Output = Self;
 
-    fn mul(self, other: f64) -> Self {
-        Self {
-            x: self.x * other,
-            y: self.y * other,
-            z: self.z * other,
-            w: self.w * other,
-        }
+    fn mul(self, other: T) -> Self {
+        Self::from(T::scale4(self.into(), other))
     }
 }
 
-impl Mul<Tuple> for Tuple {
+impl<T: Float> Mul<Tuple<T>> for Tuple<T> {
     type Output = Self;
 
     fn mul(self, other: Self) -> Self {
-        Self {
-            x: self.x * other.x,
-            y: self.y * other.y,
-            z: self.z * other.z,
-            w: self.w * other.w,
-        }
+        Self::from(T::mul4(self.into(), other.into()))
     }
 }
 
-impl Div<f64> for Tuple {
+impl<T: Float> Div<T> for Tuple<T> {
     type Output = Self;
 
-    fn div(self, other: f64) -> Self {
+    fn div(self, other: T) -> Self {
         Self {
             x: self.x / other,
             y: self.y / other,
@@ -103,22 +104,22 @@ impl Div<f64> for Tuple {
     }
 }
 
-impl AbsDiffEq for Tuple {
-    type Epsilon = f64;
+impl<T: Float> AbsDiffEq for Tuple<T> {
+    type Epsilon = T;
 
     fn default_epsilon() -> Self::Epsilon {
-        f64::EPSILON
+        T::EPSILON
     }
 
-    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
-        f64::abs_diff_eq(&self.x, &other.x, epsilon)
-            && f64::abs_diff_eq(&self.y, &other.y, epsilon)
-            && f64::abs_diff_eq(&self.z, &other.z, epsilon)
-            && f64::abs_diff_eq(&self.w, &other.w, epsilon)
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        T::abs_diff_eq(&self.x, &other.x, epsilon)
+            && T::abs_diff_eq(&self.y, &other.y, epsilon)
+            && T::abs_diff_eq(&self.z, &other.z, epsilon)
+            && T::abs_diff_eq(&self.w, &other.w, epsilon)
     }
 }
 
-impl fmt::Display for Tuple {
+impl<T: Float> fmt::Display for Tuple<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({}, {}, {}, {})", self.x, self.y, self.z, self.w)
     }
@@ -140,7 +141,7 @@ mod tests {
         assert_abs_diff_eq!(thing.x, 4.3);
         assert_abs_diff_eq!(thing.y, -4.2);
         assert_abs_diff_eq!(thing.z, 3.1);
-        assert_eq!(thing.is_vector(), false)
+        assert!(!thing.is_vector())
     }
 
     #[test]
@@ -155,7 +156,7 @@ mod tests {
         assert_abs_diff_eq!(thing.x, 4.3);
         assert_abs_diff_eq!(thing.y, -4.2);
         assert_abs_diff_eq!(thing.z, 3.1);
-        assert_eq!(thing.is_point(), false)
+        assert!(!thing.is_point())
     }
 
     #[test]
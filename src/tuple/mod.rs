@@ -1,7 +1,9 @@
 mod base;
+mod normal;
 mod point;
 mod vector;
 
 pub use base::Tuple;
+pub use normal::Normal3;
 pub use point::Point3;
 pub use vector::Vector3;
@@ -0,0 +1,99 @@
+use crate::float::Float;
+use crate::tuple::{Tuple, Vector3};
+use approx::AbsDiffEq;
+use std::fmt;
+use std::ops::{Mul, Neg};
+
+// a surface normal, it transforms with the inverse transpose of what
+// transforms the surface so that it stays perpendicular to it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Normal3<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Float> Normal3<T> {
+    pub fn normal(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn magnitude(&self) -> T {
+        self.to_vector().magnitude()
+    }
+
+    pub fn normalized(&self) -> Self {
+        Self::from(self.to_vector().normalized())
+    }
+
+    pub fn dot(&self, other: Vector3<T>) -> T {
+        self.to_vector().dot(other)
+    }
+
+    pub fn to_vector(self) -> Vector3<T> {
+        Vector3::vector(self.x, self.y, self.z)
+    }
+}
+
+impl<T: Float> From<Vector3<T>> for Normal3<T> {
+    fn from(vector: Vector3<T>) -> Self {
+        Self::normal(vector.x, vector.y, vector.z)
+    }
+}
+
+impl<T: Float> From<Normal3<T>> for Tuple<T> {
+    fn from(normal: Normal3<T>) -> Self {
+        Tuple::vector(normal.x, normal.y, normal.z)
+    }
+}
+
+// flips the side the normal faces
+impl<T: Float> Neg for Normal3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::normal(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Float> Mul<T> for Normal3<T> {
+    type Output = Self;
+
+    fn mul(self, other: T) -> Self {
+        Self::normal(self.x * other, self.y * other, self.z * other)
+    }
+}
+
+impl<T: Float> AbsDiffEq for Normal3<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        T::abs_diff_eq(&self.x, &other.x, epsilon)
+            && T::abs_diff_eq(&self.y, &other.y, epsilon)
+            && T::abs_diff_eq(&self.z, &other.z, epsilon)
+    }
+}
+
+impl<T: Float> fmt::Display for Normal3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Normal3;
+    use crate::tuple::Vector3;
+
+    #[test]
+    fn it_normalizes_normals() {
+        let normal = Normal3::normal(0., 3., 4.);
+        assert_abs_diff_eq!(normal.magnitude(), 5.);
+        assert_abs_diff_eq!(normal.normalized(), Normal3::normal(0., 0.6, 0.8));
+        assert_abs_diff_eq!(normal.dot(Vector3::vector(1., 1., 1.)), 7.);
+    }
+}
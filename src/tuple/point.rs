@@ -1,13 +1,110 @@
-use crate::tuple::Tuple;
+use crate::float::Float;
+use crate::tuple::{Tuple, Vector3};
+use approx::AbsDiffEq;
+use std::fmt;
+use std::ops::{Add, Sub};
 
-pub type Point3 = Tuple;
+// a position, w is implicitly 1 so translations move it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Point3<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
 
-impl Point3 {
-    pub fn point(x: f64, y: f64, z: f64) -> Self {
-        Self { x, y, z, w: 1.0 }
+impl<T: Float> Point3<T> {
+    pub fn point(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
     }
 
     pub fn origin() -> Self {
-        Self::point (0., 0., 0.)
+        Self::point(T::ZERO, T::ZERO, T::ZERO)
+    }
+}
+
+impl<T: Float> Add<Vector3<T>> for Point3<T> {
+    type Output = Self;
+
+    fn add(self, other: Vector3<T>) -> Self {
+        Self::point(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Float> Sub<Vector3<T>> for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, other: Vector3<T>) -> Self {
+        Self::point(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+// the vector going from `other` to self
+impl<T: Float> Sub for Point3<T> {
+    type Output = Vector3<T>;
+
+    fn sub(self, other: Self) -> Vector3<T> {
+        Vector3::vector(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Float> From<Point3<T>> for Tuple<T> {
+    fn from(point: Point3<T>) -> Self {
+        Tuple::point(point.x, point.y, point.z)
+    }
+}
+
+impl<T: Float> AbsDiffEq for Point3<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        T::abs_diff_eq(&self.x, &other.x, epsilon)
+            && T::abs_diff_eq(&self.y, &other.y, epsilon)
+            && T::abs_diff_eq(&self.z, &other.z, epsilon)
+    }
+}
+
+impl<T: Float> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Point3;
+    use crate::tuple::{Tuple, Vector3};
+
+    #[test]
+    fn it_substracts_two_points() {
+        let point1 = Point3::point(3., 2., 1.);
+        let point2 = Point3::point(5., 6., 7.);
+        assert_abs_diff_eq!(point1 - point2, Vector3::vector(-2., -4., -6.))
+    }
+
+    #[test]
+    fn it_moves_points_by_vectors() {
+        let point = Point3::point(3., -2., 5.);
+        let vector = Vector3::vector(-2., 3., 1.);
+        assert_abs_diff_eq!(point + vector, Point3::point(1., 1., 6.));
+        assert_abs_diff_eq!(point + vector - vector, point);
+    }
+
+    #[test]
+    fn it_converts_to_a_tuple_with_w_at_one() {
+        let tuple = Tuple::from(Point3::point(1., 2., 3.));
+        assert!(tuple.is_point());
+        assert_abs_diff_eq!(tuple, Tuple::point(1., 2., 3.));
+    }
+
+    #[test]
+    fn it_moves_single_precision_points() {
+        let point = Point3::<f32>::point(3., -2., 5.);
+        let vector = Vector3::vector(-2., 3., 1.);
+        assert_abs_diff_eq!(point + vector, Point3::point(1., 1., 6.));
+        assert_abs_diff_eq!((point + vector) - point, vector);
     }
 }
@@ -1,47 +1,129 @@
-use crate::tuple::Tuple;
-
-pub type Vector3 = Tuple;
+use crate::float::Float;
+use crate::tuple::{Normal3, Tuple};
+use approx::AbsDiffEq;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// a direction or a displacement, w is implicitly 0 so translations leave
+// it unchanged
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Vector3<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
 
-impl Vector3 {
-    pub fn vector(x: f64, y: f64, z: f64) -> Self {
-        Self { x, y, z, w: 0.0 }
+impl<T: Float> Vector3<T> {
+    pub fn vector(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
     }
 
-    pub fn magnitude(&self) -> f64 {
-        (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt()
+    pub fn magnitude(&self) -> T {
+        self.dot(*self).sqrt()
     }
 
     pub fn normalized(&self) -> Self {
-        let mag = self.magnitude();
-        Self {
-            x: self.x / mag,
-            y: self.y / mag,
-            z: self.z / mag,
-            w: self.w / mag,
-        }
+        *self / self.magnitude()
     }
 
-    pub fn dot(&self, other: Self) -> f64 {
-        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    pub fn dot(&self, other: Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
     }
 
-    pub fn cross(&self, other: Self) -> Tuple {
-        Tuple::vector(
+    pub fn cross(&self, other: Self) -> Self {
+        Self::vector(
             self.y * other.z - self.z * other.y,
             self.z * other.x - self.x * other.z,
             self.x * other.y - self.y * other.x,
         )
     }
 
-    pub fn reflect(&self, normal: Vector3) -> Vector3 {
-        *self - normal * 2. * self.dot(normal)
+    // mirror the vector around `normal`, which has to be normalized
+    pub fn reflect(&self, normal: Normal3<T>) -> Self {
+        let normal = normal.to_vector();
+        *self - normal * (T::from_f64(2.) * self.dot(normal))
+    }
+}
+
+impl<T: Float> Add for Vector3<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::vector(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Float> Sub for Vector3<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::vector(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Float> Neg for Vector3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::vector(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Float> Mul<T> for Vector3<T> {
+    type Output = Self;
+
+    fn mul(self, other: T) -> Self {
+        Self::vector(self.x * other, self.y * other, self.z * other)
+    }
+}
+
+// component-wise product, for scale factors
+impl<T: Float> Mul for Vector3<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::vector(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+}
+
+impl<T: Float> Div<T> for Vector3<T> {
+    type Output = Self;
+
+    fn div(self, other: T) -> Self {
+        Self::vector(self.x / other, self.y / other, self.z / other)
+    }
+}
+
+impl<T: Float> From<Vector3<T>> for Tuple<T> {
+    fn from(vector: Vector3<T>) -> Self {
+        Tuple::vector(vector.x, vector.y, vector.z)
+    }
+}
+
+impl<T: Float> AbsDiffEq for Vector3<T> {
+    type Epsilon = T;
+
+    fn default_epsilon() -> Self::Epsilon {
+        T::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        T::abs_diff_eq(&self.x, &other.x, epsilon)
+            && T::abs_diff_eq(&self.y, &other.y, epsilon)
+            && T::abs_diff_eq(&self.z, &other.z, epsilon)
+    }
+}
+
+impl<T: Float> fmt::Display for Vector3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Vector3;
-    use crate::tuple::Tuple;
+    use crate::tuple::{Normal3, Point3};
 
     #[test]
     fn it_substracts_two_vectors() {
@@ -59,9 +141,9 @@ mod tests {
 
     #[test]
     fn it_substracts_a_vector_from_a_point() {
-        let point = Vector3::point(3., 2., 1.);
+        let point = Point3::point(3., 2., 1.);
         let vector = Vector3::vector(5., 6., 7.);
-        assert_abs_diff_eq!(point - vector, Vector3::point(-2., -4., -6.))
+        assert_abs_diff_eq!(point - vector, Point3::point(-2., -4., -6.))
     }
 
     #[test]
@@ -112,14 +194,14 @@ mod tests {
     #[test]
     fn it_reflects_a_vector_approachings_at_45_degrees() {
         let v = Vector3::vector(1., -1., 0.);
-        let n = Vector3::vector(0., 1., 0.);
+        let n = Normal3::normal(0., 1., 0.);
         assert_abs_diff_eq!(v.reflect(n), Vector3::vector(1., 1., 0.));
     }
 
     #[test]
     fn it_reflects_a_vector_off_a_slanted_surface() {
         let v = Vector3::vector(0., -1., 0.);
-        let n = Vector3::vector(f64::sqrt(2.)/2., f64::sqrt(2.)/2., 0.);
+        let n = Normal3::normal(f64::sqrt(2.)/2., f64::sqrt(2.)/2., 0.);
         assert_abs_diff_eq!(v.reflect(n), Vector3::vector(1., 0., 0.));
     }
 